@@ -1,4 +1,5 @@
 const path = require("path");
+const crypto = require("crypto");
 
 const {
   Orchestrator,
@@ -44,34 +45,40 @@ const conductorConfig = Config.gen(
 
 // })
 
-const create_course = async (user, title, timestamp) => {
+// created_at and updated_at come from the chain, so tests only check they are set
+const without_times = ({ created_at, updated_at, ...rest }) => {
+  if (!created_at || !updated_at) {
+    throw new Error("entry is missing its created_at or updated_at time");
+  }
+  return rest;
+}
+
+const create_course = async (user, title) => {
   const course_addr = await user.call(
     "course_dna",
     "courses",
     "create_course",
     {
-      title,
-      timestamp
+      title
     }
   );
   return course_addr;
 }
 
-const create_module = async (user, title, course_address, timestamp) => {
+const create_module = async (user, title, course_address) => {
   const module_addr = await user.call(
     "course_dna",
     "courses",
     "create_module",
     {
       title,
-      course_address,
-      timestamp
+      course_address
     }
   );
   return module_addr;
 }
 
-const create_content = async (user, name, module_address, url, timestamp, description) => {
+const create_content = async (user, name, module_address, url, description) => {
   const content_addr = await user.call(
     "course_dna",
     "courses", 
@@ -80,7 +87,6 @@ const create_content = async (user, name, module_address, url, timestamp, descri
       name,
       module_address,
       url,
-      timestamp,
       description
     }
   );
@@ -93,7 +99,7 @@ orchestrator.registerScenario("Scenario1: Create new course", async (s, t) => {
     true
   );
 
-  const course_addr = await create_course(alice, "course test 1", 123);
+  const course_addr = await create_course(alice, "course test 1");
   t.ok(course_addr.Ok);
   await s.consistency();
 
@@ -101,11 +107,15 @@ orchestrator.registerScenario("Scenario1: Create new course", async (s, t) => {
     address: course_addr.Ok
   })
   const course = JSON.parse(courseResult.Ok.App[1]);
-  t.deepEqual(course, {
+  t.deepEqual(without_times(course), {
     title: "course test 1",
-    timestamp: 123,
     teacher_address: alice.instance("course_dna").agentAddress,
-    modules: []
+    modules: [],
+    prerequisites: [],
+    max_students: null,
+    enrollment_policy: "Open",
+    private: false,
+    content_key_version: null, price: null, forkable: true
   })
   await s.consistency();
 })
@@ -116,11 +126,11 @@ orchestrator.registerScenario("Scenario2: Get my courses", async (s, t) => {
     true
   );
 
-  const course_addr_1 = await create_course(alice, "course for scenario 2-1", 123)
+  const course_addr_1 = await create_course(alice, "course for scenario 2-1")
   t.ok(course_addr_1.Ok);
   await s.consistency();
 
-  const course_addr_2 = await create_course(alice, "course for scneario 2-2", 1234);
+  const course_addr_2 = await create_course(alice, "course for scneario 2-2");
   t.ok(course_addr_2.Ok);
   await s.consistency();
 
@@ -141,10 +151,10 @@ orchestrator.registerScenario("Scenario3: Get list of courses", async (s, t) =>
     true
   );
 
-  await create_course(alice, "course for scenario 2-1", 123)
+  await create_course(alice, "course for scenario 2-1")
   await s.consistency();
 
-  await create_course(alice, "course for scneario 2-2", 1234);
+  await create_course(alice, "course for scneario 2-2");
   await s.consistency();
 
   const courses_list = await bob.call("course_dna", "courses", "get_all_courses", { })
@@ -158,7 +168,7 @@ orchestrator.registerScenario("Scenario4: Delete course", async (s, t) => {
     true
   );
 
-  const course_addr_1 = await create_course(alice, "course for deleting", 123)
+  const course_addr_1 = await create_course(alice, "course for deleting")
   t.ok(course_addr_1.Ok);
   await s.consistency();
 
@@ -179,7 +189,7 @@ orchestrator.registerScenario("Scenario5: Enrol in course", async (s, t) => {
     true
   );
 
-  const course_addr_1 = await create_course(alice, "course for enroling", 123)
+  const course_addr_1 = await create_course(alice, "course for enroling")
   t.ok(course_addr_1.Ok);
   await s.consistency();
 
@@ -200,7 +210,7 @@ orchestrator.registerScenario("Scenario6: Update course", async (s, t) => {
     true
   );
   
-  const course_addr_1 = await create_course(alice, "course for updating", 123)
+  const course_addr_1 = await create_course(alice, "course for updating")
   t.ok(course_addr_1.Ok);
   await s.consistency();
 
@@ -216,11 +226,15 @@ orchestrator.registerScenario("Scenario6: Update course", async (s, t) => {
     address: course_addr_1.Ok
   })
   const course = JSON.parse(courseResult.Ok.App[1]);
-  t.deepEqual(course, {
+  t.deepEqual(without_times(course), {
     title: "updated course",
-    timestamp: 123,
     teacher_address: alice.instance("course_dna").agentAddress,
-    modules: []
+    modules: [],
+    prerequisites: [],
+    max_students: null,
+    enrollment_policy: "Open",
+    private: false,
+    content_key_version: null, price: null, forkable: true
   })
   await s.consistency();
 
@@ -232,11 +246,11 @@ orchestrator.registerScenario("Scbenatrio7: Create Module", async (s, t) => {
     true
   );
   
-  const course_addr = await create_course(alice, "my course", 123);
+  const course_addr = await create_course(alice, "my course");
   t.ok(course_addr.Ok);
   await s.consistency();
 
-  const module_addr = await create_module(alice, "my module", course_addr.Ok, 123);
+  const module_addr = await create_module(alice, "my module", course_addr.Ok);
   t.ok(module_addr.Ok);
   await s.consistency();
 
@@ -244,10 +258,11 @@ orchestrator.registerScenario("Scbenatrio7: Create Module", async (s, t) => {
     address: module_addr.Ok
   })
   const courseModule = JSON.parse(moduleResult.Ok.App[1]);
-  t.deepEqual(courseModule, {
+  t.deepEqual(without_times(courseModule), {
     title: "my module",
     course_address: course_addr.Ok,
-    timestamp: 123
+    release_offset: null,
+    release_at: null
   })
   await s.consistency();
 
@@ -265,11 +280,11 @@ orchestrator.registerScenario("Scenario8: Update Module", async (s, t) => {
     true
   );
 
-  const course_addr = await create_course(alice, "my course", 123);
+  const course_addr = await create_course(alice, "my course");
   t.ok(course_addr.Ok);
   await s.consistency();
 
-  const module_addr = await create_module(alice, "my module", course_addr.Ok, 123);
+  const module_addr = await create_module(alice, "my module", course_addr.Ok);
   t.ok(module_addr.Ok);
   await s.consistency();
 
@@ -282,10 +297,11 @@ orchestrator.registerScenario("Scenario8: Update Module", async (s, t) => {
     address: updated_module.Ok
   })
   const courseModule = JSON.parse(moduleResult.Ok.App[1]);
-  t.deepEqual(courseModule, {
+  t.deepEqual(without_times(courseModule), {
     title: "updated module",
     course_address: course_addr.Ok,
-    timestamp: 123
+    release_offset: null,
+    release_at: null
   })
   await s.consistency();
 
@@ -299,10 +315,11 @@ orchestrator.registerScenario("Scenario8: Update Module", async (s, t) => {
     address: course.modules[0] //this is the address of the module before getting updated, should bring the updated module
   })
   const moduleByCourseResult = JSON.parse(moduleByCourse.Ok.App[1]);
-  t.deepEqual(moduleByCourseResult, {
+  t.deepEqual(without_times(moduleByCourseResult), {
     title: "updated module",
     course_address: course_addr.Ok,
-    timestamp: 123
+    release_offset: null,
+    release_at: null
   })
   await s.consistency();
 })
@@ -313,11 +330,11 @@ orchestrator.registerScenario("Scenario9: Delete module", async (s, t) => {
     true
   );
   
-  const course_addr = await create_course(alice, "my course", 123);
+  const course_addr = await create_course(alice, "my course");
   t.ok(course_addr.Ok);
   await s.consistency();
 
-  const module_addr = await create_module(alice, "my module", course_addr.Ok, 123);
+  const module_addr = await create_module(alice, "my module", course_addr.Ok);
   t.ok(module_addr.Ok);
   await s.consistency();
 
@@ -341,15 +358,15 @@ orchestrator.registerScenario("Scenario10: Create Content", async (s, t) => {
     true
   );
 
-  const course_addr = await create_course(alice, "my course", 123);
+  const course_addr = await create_course(alice, "my course");
   t.ok(course_addr.Ok);
   await s.consistency();
 
-  const module_addr = await create_module(alice, "my module", course_addr.Ok, 123);
+  const module_addr = await create_module(alice, "my module", course_addr.Ok);
   t.ok(module_addr.Ok);
   await s.consistency();
 
-  const content_addr = await create_content(alice, "my content", module_addr.Ok, "www.content.com", 123, "this is my new content");
+  const content_addr = await create_content(alice, "my content", module_addr.Ok, "www.content.com", "this is my new content");
   t.ok(content_addr.Ok);
   await s.consistency();
 
@@ -359,12 +376,12 @@ orchestrator.registerScenario("Scenario10: Create Content", async (s, t) => {
   const content = JSON.parse(contentResult.Ok.App[1]);
   await s.consistency();
 
-  t.deepEqual(content, {
+  t.deepEqual(without_times(content), {
     name: "my content",
     url: "www.content.com",
     description: "this is my new content",
-    timestamp: 123,
-    module_address: module_addr.Ok
+    module_address: module_addr.Ok,
+    key_version: null
   })
   await s.consistency();
 
@@ -376,15 +393,15 @@ orchestrator.registerScenario("Scenario11: Update Content", async (s, t) => {
     true
   );
 
-  const course_addr = await create_course(alice, "my course", 123);
+  const course_addr = await create_course(alice, "my course");
   t.ok(course_addr.Ok);
   await s.consistency();
 
-  const module_addr = await create_module(alice, "my module", course_addr.Ok, 123);
+  const module_addr = await create_module(alice, "my module", course_addr.Ok);
   t.ok(module_addr.Ok);
   await s.consistency();
 
-  const content_addr = await create_content(alice, "my content", module_addr.Ok, "www.content.com", 123, "this is my new content");
+  const content_addr = await create_content(alice, "my content", module_addr.Ok, "www.content.com", "this is my new content");
   t.ok(content_addr.Ok);
   await s.consistency();
 
@@ -403,12 +420,12 @@ orchestrator.registerScenario("Scenario11: Update Content", async (s, t) => {
   const content = JSON.parse(contentResult.Ok.App[1]);
   await s.consistency();
 
-  t.deepEqual(content, {
+  t.deepEqual(without_times(content), {
     name: "updated content",
     url: "www.updatedcontent.com",
     description: "this content has been updated",
-    timestamp: 123,
-    module_address: module_addr.Ok
+    module_address: module_addr.Ok,
+    key_version: null
   })
   await s.consistency();
 
@@ -416,12 +433,12 @@ orchestrator.registerScenario("Scenario11: Update Content", async (s, t) => {
     address: content_addr.Ok
   })
   const contentByPreviousAddr = JSON.parse(contentResultByPreviousAddr.Ok.App[1]);
-  t.deepEqual(contentByPreviousAddr, {
+  t.deepEqual(without_times(contentByPreviousAddr), {
     name: "updated content",
     url: "www.updatedcontent.com",
     description: "this content has been updated",
-    timestamp: 123,
-    module_address: module_addr.Ok
+    module_address: module_addr.Ok,
+    key_version: null
   })
   await s.consistency();
 
@@ -442,15 +459,15 @@ orchestrator.registerScenario("Scenario12: Delete content", async (s, t) => {
     true
   );
 
-  const course_addr = await create_course(alice, "my course", 123);
+  const course_addr = await create_course(alice, "my course");
   t.ok(course_addr.Ok);
   await s.consistency();
 
-  const module_addr = await create_module(alice, "my module", course_addr.Ok, 123);
+  const module_addr = await create_module(alice, "my module", course_addr.Ok);
   t.ok(module_addr.Ok);
   await s.consistency();
 
-  const content_addr = await create_content(alice, "my content", module_addr.Ok, "www.content.com", 123, "this is my new content");
+  const content_addr = await create_content(alice, "my content", module_addr.Ok, "www.content.com", "this is my new content");
   t.ok(content_addr.Ok);
   await s.consistency();
 
@@ -467,4 +484,1466 @@ orchestrator.registerScenario("Scenario12: Delete content", async (s, t) => {
 
 })
 
+orchestrator.registerScenario("Scenario13: Transfer course ownership", async (s, t) => {
+  const { alice, bob } = await s.players(
+    {alice: conductorConfig, bob: conductorConfig},
+    true
+  );
+
+  const course_addr = await create_course(alice, "course for transfer");
+  t.ok(course_addr.Ok);
+  await s.consistency();
+
+  // adding a module updates the course before it is handed over
+  const module_addr = await create_module(alice, "module before transfer", course_addr.Ok);
+  t.ok(module_addr.Ok);
+  await s.consistency();
+
+  const bob_address = bob.instance("course_dna").agentAddress;
+  const transfer_addr = await alice.call("course_dna", "courses", "propose_course_transfer", {
+    course_address: course_addr.Ok,
+    new_teacher_address: bob_address
+  })
+  t.ok(transfer_addr.Ok);
+  await s.consistency();
+
+  const proposals = await bob.call("course_dna", "courses", "get_my_transfer_proposals", {});
+  t.true(proposals.Ok.length === 1);
+  t.true(proposals.Ok[0] === transfer_addr.Ok);
+
+  const accepted = await bob.call("course_dna", "courses", "accept_course_transfer", {
+    transfer_address: transfer_addr.Ok
+  })
+  t.ok(accepted.Ok);
+  await s.consistency();
+
+  const courseResult = await bob.call("course_dna", "courses", "get_entry", {
+    address: course_addr.Ok
+  })
+  const course = JSON.parse(courseResult.Ok.App[1]);
+  t.true(course.teacher_address === bob_address);
+  t.deepEqual(course.modules, [module_addr.Ok]);
+
+  const alice_courses = await alice.call("course_dna", "courses", "get_my_courses", {});
+  t.true(alice_courses.Ok.length === 0);
+  const bob_courses = await bob.call("course_dna", "courses", "get_my_courses", {});
+  t.true(bob_courses.Ok.length === 1);
+  t.true(bob_courses.Ok[0] === course_addr.Ok);
+
+  // a transfer is used up once accepted
+  const accepted_again = await bob.call("course_dna", "courses", "accept_course_transfer", {
+    transfer_address: transfer_addr.Ok
+  })
+  t.ok(accepted_again.Err);
+
+  const alice_address = alice.instance("course_dna").agentAddress;
+  const back_addr = await bob.call("course_dna", "courses", "propose_course_transfer", {
+    course_address: course_addr.Ok,
+    new_teacher_address: alice_address
+  })
+  t.ok(back_addr.Ok);
+  await s.consistency();
+
+  const cancelled = await bob.call("course_dna", "courses", "cancel_course_transfer", { transfer_address: back_addr.Ok });
+  t.ok(cancelled.Ok);
+  await s.consistency();
+
+  const alice_proposals = await alice.call("course_dna", "courses", "get_my_transfer_proposals", {});
+  t.deepEqual(alice_proposals.Ok, []);
+  const after_cancel = await alice.call("course_dna", "courses", "accept_course_transfer", { transfer_address: back_addr.Ok });
+  t.ok(after_cancel.Err);
+})
+
+orchestrator.registerScenario("Scenario14: Course prerequisites", async (s, t) => {
+  const { alice, bob, carol } = await s.players(
+    {alice: conductorConfig, bob: conductorConfig, carol: conductorConfig},
+    true
+  );
+
+  const basics_addr = await create_course(alice, "basics");
+  const advanced_addr = await create_course(alice, "advanced");
+  t.ok(basics_addr.Ok);
+  t.ok(advanced_addr.Ok);
+  await s.consistency();
+
+  const updated = await alice.call("course_dna", "courses", "update_course_prerequisites", {
+    course_address: advanced_addr.Ok,
+    prerequisites: [basics_addr.Ok]
+  })
+  t.ok(updated.Ok);
+  await s.consistency();
+
+  // the cycle must still be found once basics has been updated
+  const basics_module = await create_module(alice, "basics module", basics_addr.Ok);
+  t.ok(basics_module.Ok);
+  await s.consistency();
+
+  const cycle = await alice.call("course_dna", "courses", "update_course_prerequisites", {
+    course_address: basics_addr.Ok,
+    prerequisites: [advanced_addr.Ok]
+  })
+  t.ok(cycle.Err);
+  await s.consistency();
+
+  const refused = await bob.call("course_dna", "courses", "enrol_in_course", {
+    course_address: advanced_addr.Ok
+  })
+  t.ok(refused.Err);
+  t.ok(JSON.stringify(refused.Err).includes(basics_addr.Ok));
+
+  const completion = await alice.call("course_dna", "courses", "issue_completion", {
+    course_address: basics_addr.Ok,
+    student_address: bob.instance("course_dna").agentAddress
+  })
+  t.ok(completion.Ok);
+  await s.consistency();
+
+  // completions issued by a previous teacher keep counting
+  const transfer_addr = await alice.call("course_dna", "courses", "propose_course_transfer", {
+    course_address: basics_addr.Ok,
+    new_teacher_address: carol.instance("course_dna").agentAddress
+  })
+  t.ok(transfer_addr.Ok);
+  await s.consistency();
+  const accepted = await carol.call("course_dna", "courses", "accept_course_transfer", {
+    transfer_address: transfer_addr.Ok
+  })
+  t.ok(accepted.Ok);
+  await s.consistency();
+
+  const enrol = await bob.call("course_dna", "courses", "enrol_in_course", {
+    course_address: advanced_addr.Ok
+  })
+  t.ok(enrol.Ok);
+  await s.consistency();
+
+  const graph = await bob.call("course_dna", "courses", "get_prerequisite_graph", {
+    course_address: advanced_addr.Ok
+  })
+  t.true(graph.Ok.courses.length === 2);
+  t.deepEqual(graph.Ok.edges, [{
+    course_address: advanced_addr.Ok,
+    prerequisite_address: basics_addr.Ok
+  }])
+
+
+  // the current teacher can revoke a completion their predecessor issued
+  const not_teacher = await bob.call("course_dna", "courses", "revoke_completion", { completion_address: completion.Ok });
+  t.ok(not_teacher.Err);
+  const revoked = await carol.call("course_dna", "courses", "revoke_completion", { completion_address: completion.Ok });
+  t.ok(revoked.Ok);
+  await s.consistency();
+  await bob.call("course_dna", "courses", "unenrol_from_course", { course_address: advanced_addr.Ok });
+  await s.consistency();
+  const missing = await bob.call("course_dna", "courses", "enrol_in_course", { course_address: advanced_addr.Ok });
+  t.ok(missing.Err);
+})
+
+orchestrator.registerScenario("Scenario15: Course capacity and waitlist", async (s, t) => {
+  const { alice, bob, carol } = await s.players(
+    {alice: conductorConfig, bob: conductorConfig, carol: conductorConfig},
+    true
+  );
+
+  const course_addr = await create_course(alice, "live cohort");
+  t.ok(course_addr.Ok);
+  await s.consistency();
+
+  const capacity = await alice.call("course_dna", "courses", "update_course_capacity", {
+    course_address: course_addr.Ok,
+    max_students: 1
+  })
+  t.ok(capacity.Ok);
+  await s.consistency();
+
+  await bob.call("course_dna", "courses", "enrol_in_course", { course_address: course_addr.Ok });
+  await s.consistency();
+  await carol.call("course_dna", "courses", "enrol_in_course", { course_address: course_addr.Ok });
+  await s.consistency();
+
+  const carol_address = carol.instance("course_dna").agentAddress;
+  let students = await alice.call("course_dna", "courses", "get_students", { course_address: course_addr.Ok });
+  t.deepEqual(students.Ok, [bob.instance("course_dna").agentAddress]);
+  let waitlist = await alice.call("course_dna", "courses", "get_waitlist", { course_address: course_addr.Ok });
+  t.deepEqual(waitlist.Ok, [carol_address]);
+
+  const waiting_twice = await carol.call("course_dna", "courses", "enrol_in_course", { course_address: course_addr.Ok });
+  t.ok(waiting_twice.Err);
+
+  // only the teacher's node can let carol in once approval is required
+  await alice.call("course_dna", "courses", "update_course_enrollment_policy", {
+    course_address: course_addr.Ok,
+    enrollment_policy: "ApprovalRequired"
+  })
+  await s.consistency();
+
+  const promoted = await bob.call("course_dna", "courses", "unenrol_from_course", { course_address: course_addr.Ok });
+  t.true(promoted.Ok === carol_address);
+  await s.consistency();
+
+  students = await alice.call("course_dna", "courses", "get_students", { course_address: course_addr.Ok });
+  t.deepEqual(students.Ok, [carol_address]);
+  waitlist = await alice.call("course_dna", "courses", "get_waitlist", { course_address: course_addr.Ok });
+  t.true(waitlist.Ok.length === 0);
+})
+
+orchestrator.registerScenario("Scenario16: Teacher-approved enrollment", async (s, t) => {
+  const { alice, bob, carol } = await s.players(
+    {alice: conductorConfig, bob: conductorConfig, carol: conductorConfig},
+    true
+  );
+
+  const course_addr = await create_course(alice, "approval course");
+  t.ok(course_addr.Ok);
+  await s.consistency();
+
+  const policy = await alice.call("course_dna", "courses", "update_course_enrollment_policy", {
+    course_address: course_addr.Ok,
+    enrollment_policy: "ApprovalRequired"
+  })
+  t.ok(policy.Ok);
+  await alice.call("course_dna", "courses", "update_course_capacity", {
+    course_address: course_addr.Ok,
+    max_students: 1
+  })
+  await s.consistency();
+
+  const request_addr = await bob.call("course_dna", "courses", "enrol_in_course", {
+    course_address: course_addr.Ok
+  })
+  t.ok(request_addr.Ok);
+  await s.consistency();
+
+  let students = await alice.call("course_dna", "courses", "get_students", { course_address: course_addr.Ok });
+  t.true(students.Ok.length === 0);
+
+  const requests = await alice.call("course_dna", "courses", "get_enrollment_requests", { course_address: course_addr.Ok });
+  t.deepEqual(requests.Ok, [request_addr.Ok]);
+
+  const approved = await alice.call("course_dna", "courses", "approve_enrollment", { request_address: request_addr.Ok });
+  t.ok(approved.Ok);
+  await s.consistency();
+
+  students = await alice.call("course_dna", "courses", "get_students", { course_address: course_addr.Ok });
+  t.deepEqual(students.Ok, [bob.instance("course_dna").agentAddress]);
+  let pending = await alice.call("course_dna", "courses", "get_enrollment_requests", { course_address: course_addr.Ok });
+  t.true(pending.Ok.length === 0);
+
+  // a full course refuses the approval and leaves the request pending
+  const carol_request = await carol.call("course_dna", "courses", "enrol_in_course", {
+    course_address: course_addr.Ok
+  })
+  t.ok(carol_request.Ok);
+  await s.consistency();
+
+  const full = await alice.call("course_dna", "courses", "approve_enrollment", { request_address: carol_request.Ok });
+  t.ok(full.Err);
+  await s.consistency();
+
+  pending = await alice.call("course_dna", "courses", "get_enrollment_requests", { course_address: course_addr.Ok });
+  t.deepEqual(pending.Ok, [carol_request.Ok]);
+  students = await alice.call("course_dna", "courses", "get_students", { course_address: course_addr.Ok });
+  t.deepEqual(students.Ok, [bob.instance("course_dna").agentAddress]);
+
+  // once the course is invite only, an earlier approval no longer lets bob back in
+  await alice.call("course_dna", "courses", "update_course_enrollment_policy", {
+    course_address: course_addr.Ok,
+    enrollment_policy: "InviteOnly"
+  })
+  await bob.call("course_dna", "courses", "unenrol_from_course", { course_address: course_addr.Ok });
+  await s.consistency();
+  const closed = await alice.call("course_dna", "courses", "approve_enrollment", { request_address: carol_request.Ok });
+  t.ok(closed.Err);
+  const rejoin = await bob.call("course_dna", "courses", "enrol_in_course", { course_address: course_addr.Ok });
+  t.ok(rejoin.Err);
+})
+
+orchestrator.registerScenario("Scenario17: Invite codes for private courses", async (s, t) => {
+  const { alice, bob, carol } = await s.players(
+    {alice: conductorConfig, bob: conductorConfig, carol: conductorConfig},
+    true
+  );
+
+  const course_addr = await create_course(alice, "private course");
+  t.ok(course_addr.Ok);
+  await s.consistency();
+
+  await alice.call("course_dna", "courses", "update_course_visibility", { course_address: course_addr.Ok, private: true });
+  await s.consistency();
+
+  // a private course is invite only, so nobody can enrol on their own
+  const reopened = await alice.call("course_dna", "courses", "update_course_enrollment_policy", {
+    course_address: course_addr.Ok,
+    enrollment_policy: "Open"
+  })
+  t.ok(reopened.Err);
+  const self_enrol = await carol.call("course_dna", "courses", "enrol_in_course", { course_address: course_addr.Ok });
+  t.ok(self_enrol.Err);
+
+  const module_addr = await create_module(alice, "private module", course_addr.Ok);
+  await s.consistency();
+  const content_addr = await create_content(alice, "secret", module_addr.Ok, "www.secret.com", "private lesson");
+  await s.consistency();
+
+  const denied = await bob.call("course_dna", "courses", "get_content", { content_address: content_addr.Ok });
+  t.ok(denied.Err);
+
+  // the DHT only holds the contents of a private course encrypted
+  const raw = await carol.call("course_dna", "courses", "get_entry", { address: content_addr.Ok });
+  t.false(JSON.stringify(raw).includes("www.secret.com"));
+
+  const code = await alice.call("course_dna", "courses", "create_invite_code", { course_address: course_addr.Ok, max_uses: 1 });
+  t.ok(code.Ok);
+
+  const claim = await bob.call("course_dna", "courses", "redeem_invite_code", { course_address: course_addr.Ok, code: code.Ok });
+  t.ok(claim.Ok);
+  await s.consistency();
+
+  const content = await bob.call("course_dna", "courses", "get_content", { content_address: content_addr.Ok });
+  t.equal(content.Ok.name, "secret");
+  t.equal(content.Ok.url, "www.secret.com");
+
+  const used_up = await carol.call("course_dna", "courses", "redeem_invite_code", { course_address: course_addr.Ok, code: code.Ok });
+  t.ok(used_up.Err);
+
+  const codes = await alice.call("course_dna", "courses", "get_invite_codes", { course_address: course_addr.Ok });
+  t.deepEqual(codes.Ok, [{ code: code.Ok, max_uses: 1, uses: 1, revoked: false }]);
+
+  const revoked = await alice.call("course_dna", "courses", "revoke_course_access", {
+    course_address: course_addr.Ok,
+    student_address: bob.instance("course_dna").agentAddress
+  })
+  t.true(revoked.Ok.length === 1);
+  await s.consistency();
+
+  const after_revoke = await bob.call("course_dna", "courses", "get_content", { content_address: content_addr.Ok });
+  t.ok(after_revoke.Err);
+
+  // the same code does not hand a revoked grant back
+  const redeem_again = await bob.call("course_dna", "courses", "redeem_invite_code", { course_address: course_addr.Ok, code: code.Ok });
+  t.ok(redeem_again.Err);
+})
+
+orchestrator.registerScenario("Scenario18: Encrypted content", async (s, t) => {
+  const { alice, bob, carol } = await s.players(
+    {alice: conductorConfig, bob: conductorConfig, carol: conductorConfig},
+    true
+  );
+
+  const course_addr = await create_course(alice, "encrypted course");
+  await s.consistency();
+  const module_addr = await create_module(alice, "module", course_addr.Ok);
+  await s.consistency();
+
+  const encrypted = await alice.call("course_dna", "courses", "update_course_encryption", { course_address: course_addr.Ok, encrypted: true });
+  t.ok(encrypted.Ok);
+  await s.consistency();
+
+  const content_addr = await create_content(alice, "paid lesson", module_addr.Ok, "www.paid.com", "only for students");
+  t.ok(content_addr.Ok);
+  await s.consistency();
+
+  const raw = await carol.call("course_dna", "courses", "get_entry", { address: content_addr.Ok });
+  const stored = JSON.parse(raw.Ok.App[1]);
+  t.equal(stored.key_version, 1);
+  t.notEqual(stored.url, "www.paid.com");
+
+  const not_enrolled = await carol.call("course_dna", "courses", "get_content", { content_address: content_addr.Ok });
+  t.ok(not_enrolled.Err);
+
+  await bob.call("course_dna", "courses", "enrol_in_course", { course_address: course_addr.Ok });
+  await carol.call("course_dna", "courses", "enrol_in_course", { course_address: course_addr.Ok });
+  await s.consistency();
+
+  const content = await bob.call("course_dna", "courses", "get_content", { content_address: content_addr.Ok });
+  t.equal(content.Ok.url, "www.paid.com");
+  t.equal(content.Ok.description, "only for students");
+
+  const removed = await alice.call("course_dna", "courses", "remove_student", {
+    course_address: course_addr.Ok,
+    student_address: bob.instance("course_dna").agentAddress
+  })
+  t.ok(removed.Ok === null);
+  await s.consistency();
+
+  const rotated = await carol.call("course_dna", "courses", "get_entry", { address: content_addr.Ok });
+  t.equal(JSON.parse(rotated.Ok.App[1]).key_version, 2);
+
+  const after_removal = await bob.call("course_dna", "courses", "get_content", { content_address: content_addr.Ok });
+  t.ok(after_removal.Err);
+  const still_enrolled = await carol.call("course_dna", "courses", "get_content", { content_address: content_addr.Ok });
+  t.equal(still_enrolled.Ok.url, "www.paid.com");
+
+  // the new teacher derives keys of their own, so a transfer re-keys the contents
+  const transfer_addr = await alice.call("course_dna", "courses", "propose_course_transfer", {
+    course_address: course_addr.Ok,
+    new_teacher_address: bob.instance("course_dna").agentAddress
+  })
+  await s.consistency();
+  const accepted = await bob.call("course_dna", "courses", "accept_course_transfer", { transfer_address: transfer_addr.Ok });
+  t.ok(accepted.Ok);
+  await s.consistency();
+
+  const rekeyed = await carol.call("course_dna", "courses", "get_entry", { address: content_addr.Ok });
+  t.equal(JSON.parse(rekeyed.Ok.App[1]).key_version, 3);
+  const new_teacher = await bob.call("course_dna", "courses", "get_content", { content_address: content_addr.Ok });
+  t.equal(new_teacher.Ok.url, "www.paid.com");
+  const after_transfer = await carol.call("course_dna", "courses", "get_content", { content_address: content_addr.Ok });
+  t.equal(after_transfer.Ok.url, "www.paid.com");
+
+  // a student who leaves on their own is also left behind by a new key
+  const left = await carol.call("course_dna", "courses", "unenrol_from_course", { course_address: course_addr.Ok });
+  t.ok(left.Ok === null);
+  await s.consistency();
+  const rekeyed_on_leave = await alice.call("course_dna", "courses", "get_entry", { address: content_addr.Ok });
+  t.equal(JSON.parse(rekeyed_on_leave.Ok.App[1]).key_version, 4);
+})
+
+orchestrator.registerScenario("Scenario19: Purchase course", async (s, t) => {
+  const { alice, bob } = await s.players(
+    {alice: conductorConfig, bob: conductorConfig},
+    true
+  );
+  const alice_addr = alice.instance("course_dna").agentAddress;
+  const bob_addr = bob.instance("course_dna").agentAddress;
+
+  const course_addr = await create_course(alice, "paid course");
+  await s.consistency();
+
+  const priced = await alice.call("course_dna", "courses", "update_course_price", { course_address: course_addr.Ok, price: 30 });
+  t.ok(priced.Ok);
+  await s.consistency();
+
+  const free_enrol = await bob.call("course_dna", "courses", "enrol_in_course", { course_address: course_addr.Ok });
+  t.ok(free_enrol.Err);
+
+  const purchase = await bob.call("course_dna", "courses", "purchase_course", { course_address: course_addr.Ok });
+  t.ok(purchase.Ok);
+  await s.consistency();
+
+  const students = await alice.call("course_dna", "courses", "get_students", { course_address: course_addr.Ok });
+  t.deepEqual(students.Ok, [bob_addr]);
+
+  const bob_balance = await bob.call("course_dna", "ledger", "get_balance", { agent_address: bob_addr });
+  t.equal(bob_balance.Ok, -30);
+  const alice_balance = await bob.call("course_dna", "ledger", "get_balance", { agent_address: alice_addr });
+  t.equal(alice_balance.Ok, 30);
+
+  const twice = await bob.call("course_dna", "courses", "purchase_course", { course_address: course_addr.Ok });
+  t.ok(twice.Err);
+
+  const expensive_addr = await create_course(alice, "expensive course");
+  await s.consistency();
+  await alice.call("course_dna", "courses", "update_course_price", { course_address: expensive_addr.Ok, price: 80 });
+  await s.consistency();
+
+  const over_limit = await bob.call("course_dna", "courses", "purchase_course", { course_address: expensive_addr.Ok });
+  t.ok(over_limit.Err);
+
+  // the enrollment policy is checked before anything is paid
+  const invite_only_addr = await create_course(alice, "invite only paid course");
+  await s.consistency();
+  await alice.call("course_dna", "courses", "update_course_price", { course_address: invite_only_addr.Ok, price: 10 });
+  await alice.call("course_dna", "courses", "update_course_enrollment_policy", {
+    course_address: invite_only_addr.Ok,
+    enrollment_policy: "InviteOnly"
+  })
+  await s.consistency();
+
+  const invite_only = await bob.call("course_dna", "courses", "purchase_course", { course_address: invite_only_addr.Ok });
+  t.ok(invite_only.Err);
+  await s.consistency();
+  const unchanged = await bob.call("course_dna", "ledger", "get_balance", { agent_address: bob_addr });
+  t.equal(unchanged.Ok, -30);
+
+  const unenrolled = await bob.call("course_dna", "courses", "unenrol_from_course", { course_address: course_addr.Ok });
+  t.ok(unenrolled.Ok === null);
+  await s.consistency();
+  const after = await alice.call("course_dna", "courses", "get_students", { course_address: course_addr.Ok });
+  t.deepEqual(after.Ok, []);
+
+  // the earlier payment still covers the course, nothing is charged again
+  const again = await bob.call("course_dna", "courses", "purchase_course", { course_address: course_addr.Ok });
+  t.ok(again.Ok);
+  await s.consistency();
+  const charged_once = await bob.call("course_dna", "ledger", "get_balance", { agent_address: bob_addr });
+  t.equal(charged_once.Ok, -30);
+
+  // amounts that do not fit a signed balance are rejected
+  const huge = await bob.call("course_dna", "ledger", "transfer", {
+    receiver_address: alice_addr,
+    amount: 9223372036854775808,
+    reference: "overflow"
+  });
+  t.ok(huge.Err);
+})
+
+orchestrator.registerScenario("Scenario20: Cohorts", async (s, t) => {
+  const { alice, bob, carol } = await s.players(
+    {alice: conductorConfig, bob: conductorConfig, carol: conductorConfig},
+    true
+  );
+  const bob_addr = bob.instance("course_dna").agentAddress;
+  const carol_addr = carol.instance("course_dna").agentAddress;
+
+  const course_addr = await create_course(alice, "termly course");
+  await s.consistency();
+
+  const bad_dates = await alice.call("course_dna", "courses", "create_cohort", {
+    course_address: course_addr.Ok, title: "backwards", start_date: 200, end_date: 100, max_students: null
+  });
+  t.ok(bad_dates.Err);
+
+  const spring = await alice.call("course_dna", "courses", "create_cohort", {
+    course_address: course_addr.Ok, title: "spring", start_date: 4102444800, end_date: 4118083200, max_students: 1
+  });
+  const autumn = await alice.call("course_dna", "courses", "create_cohort", {
+    course_address: course_addr.Ok, title: "autumn", start_date: 4133980800, end_date: 4149619200, max_students: null
+  });
+  t.ok(spring.Ok);
+  t.ok(autumn.Ok);
+  await s.consistency();
+
+  const not_teacher = await bob.call("course_dna", "courses", "create_cohort", {
+    course_address: course_addr.Ok, title: "bob's", start_date: 4102444800, end_date: 4118083200, max_students: null
+  });
+  t.ok(not_teacher.Err);
+
+  const cohorts = await alice.call("course_dna", "courses", "get_cohorts", { course_address: course_addr.Ok });
+  t.equal(cohorts.Ok.length, 2);
+
+  const past = await alice.call("course_dna", "courses", "create_cohort", {
+    course_address: course_addr.Ok, title: "last year", start_date: 100, end_date: 200, max_students: null
+  });
+  await s.consistency();
+  const too_late = await bob.call("course_dna", "courses", "enrol_in_course", { course_address: course_addr.Ok, cohort_address: past.Ok });
+  t.ok(too_late.Err);
+
+  const no_cohort = await bob.call("course_dna", "courses", "enrol_in_course", { course_address: course_addr.Ok, cohort_address: null });
+  t.ok(no_cohort.Err);
+
+  const bob_enrol = await bob.call("course_dna", "courses", "enrol_in_course", { course_address: course_addr.Ok, cohort_address: spring.Ok });
+  t.ok(bob_enrol.Ok);
+  await s.consistency();
+
+  const spring_full = await carol.call("course_dna", "courses", "enrol_in_course", { course_address: course_addr.Ok, cohort_address: spring.Ok });
+  t.ok(spring_full.Err);
+  const carol_enrol = await carol.call("course_dna", "courses", "enrol_in_course", { course_address: course_addr.Ok, cohort_address: autumn.Ok });
+  t.ok(carol_enrol.Ok);
+  await s.consistency();
+
+  // a cohort only lists students for its own course
+  const other_course = await create_course(alice, "other course");
+  await s.consistency();
+  const wrong_course = await alice.call("course_dna", "courses", "get_students", { course_address: other_course.Ok, cohort_address: spring.Ok });
+  t.ok(wrong_course.Err);
+
+  const all_students = await alice.call("course_dna", "courses", "get_students", { course_address: course_addr.Ok, cohort_address: null });
+  t.equal(all_students.Ok.length, 2);
+  const spring_students = await alice.call("course_dna", "courses", "get_students", { course_address: course_addr.Ok, cohort_address: spring.Ok });
+  t.deepEqual(spring_students.Ok, [bob_addr]);
+
+  const moved_by_student = await bob.call("course_dna", "courses", "move_student", { cohort_address: autumn.Ok, student_address: bob_addr });
+  t.ok(moved_by_student.Err);
+  const moved = await alice.call("course_dna", "courses", "move_student", { cohort_address: autumn.Ok, student_address: bob_addr });
+  t.ok(moved.Ok);
+  await s.consistency();
+
+  const spring_after = await alice.call("course_dna", "courses", "get_students", { course_address: course_addr.Ok, cohort_address: spring.Ok });
+  t.deepEqual(spring_after.Ok, []);
+  const autumn_after = await alice.call("course_dna", "courses", "get_students", { course_address: course_addr.Ok, cohort_address: autumn.Ok });
+  t.equal(autumn_after.Ok.length, 2);
+  t.ok(autumn_after.Ok.includes(carol_addr));
+
+  // the cohort is kept through an approval
+  const approval_course = await create_course(alice, "approved cohorts");
+  await s.consistency();
+  await alice.call("course_dna", "courses", "update_course_enrollment_policy", {
+    course_address: approval_course.Ok,
+    enrollment_policy: "ApprovalRequired"
+  })
+  const summer = await alice.call("course_dna", "courses", "create_cohort", {
+    course_address: approval_course.Ok, title: "summer", start_date: 4102444800, end_date: 4118083200, max_students: null
+  });
+  await s.consistency();
+  const request = await carol.call("course_dna", "courses", "enrol_in_course", { course_address: approval_course.Ok, cohort_address: summer.Ok });
+  t.ok(request.Ok);
+  await s.consistency();
+  const approved = await alice.call("course_dna", "courses", "approve_enrollment", { request_address: request.Ok });
+  t.ok(approved.Ok);
+  await s.consistency();
+  const summer_students = await alice.call("course_dna", "courses", "get_students", { course_address: approval_course.Ok, cohort_address: summer.Ok });
+  t.deepEqual(summer_students.Ok, [carol_addr]);
+
+  // and through a place on the waitlist
+  const waitlist_course = await create_course(alice, "waitlisted cohorts");
+  await s.consistency();
+  await alice.call("course_dna", "courses", "update_course_capacity", { course_address: waitlist_course.Ok, max_students: 1 });
+  const winter = await alice.call("course_dna", "courses", "create_cohort", {
+    course_address: waitlist_course.Ok, title: "winter", start_date: 4102444800, end_date: 4118083200, max_students: null
+  });
+  await s.consistency();
+  await bob.call("course_dna", "courses", "enrol_in_course", { course_address: waitlist_course.Ok, cohort_address: winter.Ok });
+  await s.consistency();
+  await carol.call("course_dna", "courses", "enrol_in_course", { course_address: waitlist_course.Ok, cohort_address: winter.Ok });
+  await s.consistency();
+  const promoted = await bob.call("course_dna", "courses", "unenrol_from_course", { course_address: waitlist_course.Ok });
+  t.equal(promoted.Ok, carol_addr);
+  await s.consistency();
+  const winter_students = await alice.call("course_dna", "courses", "get_students", { course_address: waitlist_course.Ok, cohort_address: winter.Ok });
+  t.deepEqual(winter_students.Ok, [carol_addr]);
+})
+
+orchestrator.registerScenario("Scenario21: Drip scheduled modules", async (s, t) => {
+  const { alice, bob } = await s.players(
+    {alice: conductorConfig, bob: conductorConfig},
+    true
+  );
+
+  const course_addr = await create_course(alice, "weekly course");
+  await s.consistency();
+  const week1 = await create_module(alice, "week 1", course_addr.Ok);
+  await s.consistency();
+  const week2 = await create_module(alice, "week 2", course_addr.Ok);
+  await s.consistency();
+  const launch = await create_module(alice, "launch", course_addr.Ok);
+  await s.consistency();
+  const content_addr = await create_content(alice, "week 2 lesson", week2.Ok, "www.week2.com", "second week");
+  await s.consistency();
+
+  const both = await alice.call("course_dna", "courses", "update_module_release", {
+    module_address: week2.Ok, release_offset: 604800, release_at: 1000
+  });
+  t.ok(both.Err);
+
+  const weekly = await alice.call("course_dna", "courses", "update_module_release", {
+    module_address: week2.Ok, release_offset: 604800, release_at: null
+  });
+  t.ok(weekly.Ok);
+  const dated = await alice.call("course_dna", "courses", "update_module_release", {
+    module_address: launch.Ok, release_offset: null, release_at: 4102444800
+  });
+  t.ok(dated.Ok);
+  await s.consistency();
+
+  await bob.call("course_dna", "courses", "enrol_in_course", { course_address: course_addr.Ok });
+  await s.consistency();
+
+  const teacher_modules = await alice.call("course_dna", "courses", "get_modules", { course_address: course_addr.Ok });
+  t.equal(teacher_modules.Ok.length, 3);
+  const student_modules = await bob.call("course_dna", "courses", "get_modules", { course_address: course_addr.Ok });
+  t.deepEqual(student_modules.Ok, [week1.Ok]);
+
+  const locked_module = await bob.call("course_dna", "courses", "get_module", { module_address: week2.Ok });
+  t.ok(locked_module.Err);
+  const locked_contents = await bob.call("course_dna", "courses", "get_contents", { module_address: week2.Ok });
+  t.deepEqual(locked_contents.Ok, []);
+  const locked_content = await bob.call("course_dna", "courses", "get_content", { content_address: content_addr.Ok });
+  t.ok(locked_content.Err);
+  const raw_module = await bob.call("course_dna", "courses", "get_entry", { address: week2.Ok });
+  t.ok(raw_module.Err);
+  const raw_content = await bob.call("course_dna", "courses", "get_entry", { address: content_addr.Ok });
+  t.ok(raw_content.Err);
+  const raw_course = await bob.call("course_dna", "courses", "get_entry", { address: course_addr.Ok });
+  t.deepEqual(JSON.parse(raw_course.Ok.App[1]).modules, [week1.Ok]);
+
+  const teacher_content = await alice.call("course_dna", "courses", "get_content", { content_address: content_addr.Ok });
+  t.equal(teacher_content.Ok.url, "www.week2.com");
+
+  await alice.call("course_dna", "courses", "update_module_release", {
+    module_address: week2.Ok, release_offset: 0, release_at: null
+  });
+  await s.consistency();
+  const released = await bob.call("course_dna", "courses", "get_module", { module_address: week2.Ok });
+  t.equal(released.Ok.title, "week 2");
+
+  // a release opens for a student who only browses in the meantime
+  await alice.call("course_dna", "courses", "update_module_release", {
+    module_address: week2.Ok, release_offset: 2, release_at: null
+  });
+  await s.consistency();
+  await new Promise(resolve => setTimeout(resolve, 3000));
+  const opened = await bob.call("course_dna", "courses", "get_modules", { course_address: course_addr.Ok });
+  t.deepEqual(opened.Ok, [week1.Ok, week2.Ok]);
+
+  const out_of_range = await alice.call("course_dna", "courses", "update_module_release", {
+    module_address: week2.Ok, release_offset: null, release_at: 9007199254740991
+  });
+  t.ok(out_of_range.Err);
+})
+
+orchestrator.registerScenario("Scenario22: Times come from the chain header", async (s, t) => {
+  const { alice } = await s.players(
+    {alice: conductorConfig},
+    true
+  );
+
+  const course_addr = await create_course(alice, "timed course");
+  await s.consistency();
+  const created = JSON.parse((await alice.call("course_dna", "courses", "get_entry", { address: course_addr.Ok })).Ok.App[1]);
+  t.equal(created.created_at, created.updated_at);
+
+  const module_addr = await create_module(alice, "timed module", course_addr.Ok);
+  await s.consistency();
+
+  const updated = JSON.parse((await alice.call("course_dna", "courses", "get_entry", { address: course_addr.Ok })).Ok.App[1]);
+  t.equal(updated.created_at, created.created_at);
+  t.ok(Date.parse(updated.updated_at) >= Date.parse(created.updated_at));
+
+  const module = JSON.parse((await alice.call("course_dna", "courses", "get_entry", { address: module_addr.Ok })).Ok.App[1]);
+  t.ok(Date.parse(module.created_at) >= Date.parse(created.created_at));
+})
+
+orchestrator.registerScenario("Scenario23: Gradebook", async (s, t) => {
+  const { alice, bob, carol } = await s.players(
+    {alice: conductorConfig, bob: conductorConfig, carol: conductorConfig},
+    true
+  );
+  const bob_addr = bob.instance("course_dna").agentAddress;
+  const carol_addr = carol.instance("course_dna").agentAddress;
+
+  const course_addr = await create_course(alice, "graded course");
+  await s.consistency();
+  const module_addr = await create_module(alice, "graded module", course_addr.Ok);
+  await s.consistency();
+
+  const scheme = await alice.call("course_dna", "courses", "update_grading_scheme", {
+    course_address: course_addr.Ok,
+    categories: [{ name: "quizzes", weight: 1 }, { name: "exam", weight: 3 }]
+  });
+  t.ok(scheme.Ok);
+
+  await bob.call("course_dna", "courses", "enrol_in_course", { course_address: course_addr.Ok });
+  await carol.call("course_dna", "courses", "enrol_in_course", { course_address: course_addr.Ok });
+  await s.consistency();
+
+  const unknown = await alice.call("course_dna", "courses", "grade_student", {
+    module_address: module_addr.Ok, student_address: bob_addr, category: "homework", score: 5, max_score: 10
+  });
+  t.ok(unknown.Err);
+  const by_student = await bob.call("course_dna", "courses", "grade_student", {
+    module_address: module_addr.Ok, student_address: bob_addr, category: "exam", score: 10, max_score: 10
+  });
+  t.ok(by_student.Err);
+
+  await alice.call("course_dna", "courses", "grade_student", {
+    module_address: module_addr.Ok, student_address: bob_addr, category: "quizzes", score: 5, max_score: 10
+  });
+  await alice.call("course_dna", "courses", "grade_student", {
+    module_address: module_addr.Ok, student_address: bob_addr, category: "exam", score: 60, max_score: 100
+  });
+  await s.consistency();
+  const regraded = await alice.call("course_dna", "courses", "grade_student", {
+    module_address: module_addr.Ok, student_address: bob_addr, category: "exam", score: 80, max_score: 100
+  });
+  t.ok(regraded.Ok);
+  await s.consistency();
+
+  const my_grades = await bob.call("course_dna", "courses", "get_my_grades", { course_address: course_addr.Ok });
+  t.equal(my_grades.Ok.items.length, 2);
+  t.equal(my_grades.Ok.total, 72.5);
+  const carol_own = await carol.call("course_dna", "courses", "get_my_grades", { course_address: course_addr.Ok });
+  t.deepEqual(carol_own.Ok.items, []);
+
+  const not_teacher = await bob.call("course_dna", "courses", "get_gradebook", { course_address: course_addr.Ok });
+  t.ok(not_teacher.Err);
+
+  const gradebook = await alice.call("course_dna", "courses", "get_gradebook", { course_address: course_addr.Ok });
+  t.equal(gradebook.Ok.students.length, 2);
+  const carol_grades = gradebook.Ok.students.find(student => student.student_address === carol_addr);
+  t.equal(carol_grades.total, null);
+
+  const csv = await alice.call("course_dna", "courses", "export_gradebook_csv", { course_address: course_addr.Ok });
+  const lines = csv.Ok.split("\n");
+  t.equal(lines[0], "student,quizzes,exam,total");
+  t.ok(lines.includes(`${bob_addr},50.00,80.00,72.50`));
+  t.ok(lines.includes(`${carol_addr},,,`));
+})
+
+orchestrator.registerScenario("Scenario24: Course analytics", async (s, t) => {
+  const { alice, bob, carol, dave } = await s.players(
+    {alice: conductorConfig, bob: conductorConfig, carol: conductorConfig, dave: conductorConfig},
+    true
+  );
+
+  const course_addr = await create_course(alice, "measured course");
+  await s.consistency();
+  const module_addr = await create_module(alice, "measured module", course_addr.Ok);
+  await s.consistency();
+  const first = await create_content(alice, "first", module_addr.Ok, "www.first.com", "first lesson");
+  await s.consistency();
+  const second = await create_content(alice, "second", module_addr.Ok, "www.second.com", "second lesson");
+  await s.consistency();
+
+  await bob.call("course_dna", "courses", "enrol_in_course", { course_address: course_addr.Ok });
+  await carol.call("course_dna", "courses", "enrol_in_course", { course_address: course_addr.Ok });
+  await s.consistency();
+
+  await bob.call("course_dna", "courses", "get_content", { content_address: first.Ok });
+  await bob.call("course_dna", "courses", "get_content", { content_address: first.Ok });
+  await carol.call("course_dna", "courses", "get_content", { content_address: first.Ok });
+  await carol.call("course_dna", "courses", "get_content", { content_address: second.Ok });
+  await alice.call("course_dna", "courses", "get_content", { content_address: second.Ok });
+  await dave.call("course_dna", "courses", "get_content", { content_address: first.Ok });
+  await s.consistency();
+
+  const not_teacher = await bob.call("course_dna", "courses", "get_course_analytics", { course_address: course_addr.Ok });
+  t.ok(not_teacher.Err);
+
+  const analytics = await alice.call("course_dna", "courses", "get_course_analytics", { course_address: course_addr.Ok });
+  t.equal(analytics.Ok.total_students, 2);
+  t.equal(analytics.Ok.enrollments_by_day.reduce((sum, bucket) => sum + bucket.count, 0), 2);
+  t.equal(analytics.Ok.enrollments_by_week.reduce((sum, bucket) => sum + bucket.count, 0), 2);
+  t.ok(/^\d{4}-\d{2}-\d{2}$/.test(analytics.Ok.enrollments_by_week[0].period_start));
+
+  const views = {};
+  analytics.Ok.contents.forEach(content => views[content.content_address] = content.views);
+  t.equal(views[first.Ok], 2);
+  t.equal(views[second.Ok], 1);
+  t.equal(analytics.Ok.drop_off, first.Ok);
+})
+
+orchestrator.registerScenario("Scenario25: Membership invitations", async (s, t) => {
+  const { alice, bob } = await s.players(
+    {alice: conductorConfig, bob: conductorConfig},
+    true
+  );
+  const alice_addr = alice.instance("course_dna").agentAddress;
+  const newcomer = "HcSCiUvMpx8Cxwd6rpgeT6Yc7z5Ebc6ca8DmbO4vSBvuyq5jdmaUJ5avDhstzpa";
+
+  const before = await alice.call("course_dna", "members", "is_member_valid", { agent_address: newcomer });
+  t.equal(before.Ok, false);
+
+  const self_invite = await alice.call("course_dna", "members", "invite_member", { invitee_address: alice_addr });
+  t.ok(self_invite.Err);
+
+  const invitation = await alice.call("course_dna", "members", "invite_member", { invitee_address: newcomer });
+  t.ok(invitation.Ok);
+  await s.consistency();
+
+  const after = await bob.call("course_dna", "members", "is_member_valid", { agent_address: newcomer });
+  t.equal(after.Ok, true);
+  const members = await bob.call("course_dna", "members", "get_valid_members", {});
+  t.ok(members.Ok.includes(newcomer));
+})
+
+orchestrator.registerScenario("Scenario26: Member roles", async (s, t) => {
+  const { alice, bob } = await s.players(
+    {alice: conductorConfig, bob: conductorConfig},
+    true
+  );
+  const alice_addr = alice.instance("course_dna").agentAddress;
+  const bob_addr = bob.instance("course_dna").agentAddress;
+
+  const alice_roles = await alice.call("course_dna", "members", "get_roles", { agent_address: alice_addr });
+  t.ok(alice_roles.Ok.includes("Admin"));
+
+  await alice.call("course_dna", "members", "revoke_role", { agent_address: bob_addr, role: "Teacher" });
+  await s.consistency();
+  const no_teacher = await bob.call("course_dna", "members", "has_role", { agent_address: bob_addr, role: "Teacher" });
+  const course_without_role = await create_course(bob, "not allowed yet");
+  t.equal(no_teacher.Ok === false, Boolean(course_without_role.Err));
+
+  const granted = await alice.call("course_dna", "members", "grant_role", { agent_address: bob_addr, role: "Moderator" });
+  t.ok(granted.Ok);
+  await s.consistency();
+  const is_moderator = await bob.call("course_dna", "members", "has_role", { agent_address: bob_addr, role: "Moderator" });
+  t.equal(is_moderator.Ok, true);
+
+  const not_admin = await bob.call("course_dna", "members", "has_role", { agent_address: bob_addr, role: "Admin" });
+  if (!not_admin.Ok) {
+    const escalation = await bob.call("course_dna", "members", "grant_role", { agent_address: bob_addr, role: "Admin" });
+    t.ok(escalation.Err);
+  }
+
+  await alice.call("course_dna", "members", "revoke_role", { agent_address: bob_addr, role: "Moderator" });
+  await s.consistency();
+  const revoked = await bob.call("course_dna", "members", "has_role", { agent_address: bob_addr, role: "Moderator" });
+  t.equal(revoked.Ok, false);
+})
+
+orchestrator.registerScenario("Scenario27: Membership revocation", async (s, t) => {
+  const { alice, bob } = await s.players(
+    {alice: conductorConfig, bob: conductorConfig},
+    true
+  );
+  const bob_addr = bob.instance("course_dna").agentAddress;
+
+  const course_addr = await create_course(bob, "bob's course");
+  await s.consistency();
+
+  const no_reason = await alice.call("course_dna", "members", "revoke_membership", { agent_address: bob_addr, reason: " " });
+  t.ok(no_reason.Err);
+
+  const revoked = await alice.call("course_dna", "members", "revoke_membership", { agent_address: bob_addr, reason: "spam" });
+  t.ok(revoked.Ok);
+  await s.consistency();
+
+  const valid = await alice.call("course_dna", "members", "is_member_valid", { agent_address: bob_addr });
+  t.equal(valid.Ok, false);
+  const members = await alice.call("course_dna", "members", "get_valid_members", {});
+  t.notOk(members.Ok.includes(bob_addr));
+
+  const new_course = await create_course(bob, "after revocation");
+  t.ok(new_course.Err);
+
+  if (course_addr.Ok) {
+    const module_addr = await create_module(bob, "after revocation", course_addr.Ok);
+    t.ok(module_addr.Err);
+
+    const still_there = await alice.call("course_dna", "courses", "get_entry", { address: course_addr.Ok });
+    t.ok(still_there.Ok);
+    const flag = await alice.call("course_dna", "courses", "get_course_flag", { course_address: course_addr.Ok });
+    t.equal(flag.Ok, "Teacher membership revoked: spam");
+  }
+})
+
+orchestrator.registerScenario("Scenario28: Member profiles", async (s, t) => {
+  const { alice, bob } = await s.players(
+    {alice: conductorConfig, bob: conductorConfig},
+    true
+  );
+  const alice_addr = alice.instance("course_dna").agentAddress;
+  const bob_addr = bob.instance("course_dna").agentAddress;
+
+  const none = await bob.call("course_dna", "members", "get_profile", { agent_address: alice_addr });
+  t.equal(none.Ok, null);
+
+  const bad_url = await alice.call("course_dna", "members", "set_profile", {
+    display_name: "Alice", bio: "", avatar_url: "ftp://avatar.png", links: []
+  });
+  t.ok(bad_url.Err);
+
+  const created = await alice.call("course_dna", "members", "set_profile", {
+    display_name: "Alice", bio: "teaches rust", avatar_url: "https://example.com/alice.png", links: []
+  });
+  t.ok(created.Ok);
+  await s.consistency();
+
+  const edited = await alice.call("course_dna", "members", "set_profile", {
+    display_name: "Alice L.", bio: "teaches rust", avatar_url: null, links: ["https://example.com"]
+  });
+  t.ok(edited.Ok);
+  await s.consistency();
+
+  const profile = await bob.call("course_dna", "members", "get_profile", { agent_address: alice_addr });
+  t.deepEqual(profile.Ok, {
+    agent_address: alice_addr,
+    display_name: "Alice L.",
+    bio: "teaches rust",
+    avatar_url: null,
+    links: ["https://example.com"]
+  });
+
+  await bob.call("course_dna", "members", "set_profile", {
+    display_name: "Bob", bio: "", avatar_url: null, links: []
+  });
+  await s.consistency();
+
+  const profiles = await alice.call("course_dna", "members", "get_profiles", { agent_addresses: [alice_addr, bob_addr] });
+  t.deepEqual(profiles.Ok.map(p => p.display_name), ["Alice L.", "Bob"]);
+})
+
+orchestrator.registerScenario("Scenario29: Member directory search", async (s, t) => {
+  const { alice, bob } = await s.players(
+    {alice: conductorConfig, bob: conductorConfig},
+    true
+  );
+  const alice_addr = alice.instance("course_dna").agentAddress;
+  const bob_addr = bob.instance("course_dna").agentAddress;
+
+  await alice.call("course_dna", "members", "set_profile", {
+    display_name: "Alice Liddell", bio: "", avatar_url: null, links: []
+  });
+  await bob.call("course_dna", "members", "set_profile", {
+    display_name: "Bob  Lidell", bio: "", avatar_url: null, links: []
+  });
+  await s.consistency();
+
+  const by_first_name = await bob.call("course_dna", "members", "search_members", {
+    query: "ALI", role_filter: null, offset: 0, limit: 10
+  });
+  t.equal(by_first_name.Ok.total, 1);
+  t.equal(by_first_name.Ok.members[0].agent_address, alice_addr);
+  t.equal(by_first_name.Ok.members[0].display_name, "Alice Liddell");
+
+  const by_last_name = await bob.call("course_dna", "members", "search_members", {
+    query: "lid", role_filter: null, offset: 0, limit: 10
+  });
+  t.equal(by_last_name.Ok.total, 2);
+  t.deepEqual(by_last_name.Ok.members.map(m => m.display_name), ["Alice Liddell", "Bob  Lidell"]);
+
+  const paged = await bob.call("course_dna", "members", "search_members", {
+    query: "lid", role_filter: null, offset: 1, limit: 1
+  });
+  t.equal(paged.Ok.total, 2);
+  t.deepEqual(paged.Ok.members.map(m => m.agent_address), [bob_addr]);
+
+  await alice.call("course_dna", "members", "grant_role", { agent_address: bob_addr, role: "Moderator" });
+  await s.consistency();
+  const moderators = await alice.call("course_dna", "members", "search_members", {
+    query: "", role_filter: "Moderator", offset: 0, limit: 10
+  });
+  t.deepEqual(moderators.Ok.members.map(m => m.agent_address), [bob_addr]);
+  t.ok(moderators.Ok.members[0].roles.includes("Moderator"));
+
+  await alice.call("course_dna", "members", "set_profile", {
+    display_name: "Alice Kingsleigh", bio: "", avatar_url: null, links: []
+  });
+  await s.consistency();
+  const renamed = await bob.call("course_dna", "members", "search_members", {
+    query: "lid", role_filter: null, offset: 0, limit: 10
+  });
+  t.deepEqual(renamed.Ok.members.map(m => m.agent_address), [bob_addr]);
+})
+
+orchestrator.registerScenario("Scenario30: Teacher summary", async (s, t) => {
+  const { alice, bob } = await s.players(
+    {alice: conductorConfig, bob: conductorConfig},
+    true
+  );
+  const alice_addr = alice.instance("course_dna").agentAddress;
+
+  await alice.call("course_dna", "members", "set_profile", {
+    display_name: "Alice", bio: "", avatar_url: null, links: []
+  });
+  const course_1 = await create_course(alice, "first course");
+  const course_2 = await create_course(alice, "second course");
+  const deleted = await create_course(alice, "deleted course");
+  await s.consistency();
+  await alice.call("course_dna", "courses", "delete_course", { course_address: deleted.Ok });
+
+  await bob.call("course_dna", "courses", "enrol_in_course", { course_address: course_1.Ok });
+  await bob.call("course_dna", "courses", "enrol_in_course", { course_address: course_2.Ok });
+  await s.consistency();
+
+  const courses = await bob.call("course_dna", "courses", "get_teacher_courses", { teacher_address: alice_addr });
+  t.equal(courses.Ok.length, 3);
+
+  const summary = await bob.call("course_dna", "courses", "get_teacher_summary", { teacher_address: alice_addr });
+  t.equal(summary.Ok.profile.display_name, "Alice");
+  t.deepEqual(summary.Ok.courses.sort(), [course_1.Ok, course_2.Ok].sort());
+  t.equal(summary.Ok.total_students, 1);
+  t.equal(summary.Ok.average_rating, null);
+})
+
+orchestrator.registerScenario("Scenario31: Student dashboard", async (s, t) => {
+  const { alice, bob } = await s.players(
+    {alice: conductorConfig, bob: conductorConfig},
+    true
+  );
+  const alice_addr = alice.instance("course_dna").agentAddress;
+
+  const course_addr = await create_course(alice, "dashboard course");
+  const module_addr = await create_module(alice, "first module", course_addr.Ok);
+  await create_content(alice, "first content", module_addr.Ok, "https://example.com/1", "one");
+  await s.consistency();
+  await bob.call("course_dna", "courses", "enrol_in_course", { course_address: course_addr.Ok });
+  await s.consistency();
+
+  const first = await bob.call("course_dna", "courses", "get_my_dashboard", {});
+  t.equal(first.Ok.last_seen, null);
+  t.equal(first.Ok.courses.length, 1);
+  t.deepEqual(first.Ok.courses[0], {
+    course_address: course_addr.Ok,
+    title: "dashboard course",
+    teacher_address: alice_addr,
+    module_count: 1,
+    content_count: 1,
+    new_modules: [],
+    new_contents: []
+  });
+
+  await new Promise(resolve => setTimeout(resolve, 1500));
+  const new_module = await create_module(alice, "second module", course_addr.Ok);
+  const new_content = await create_content(alice, "second content", new_module.Ok, "https://example.com/2", "two");
+  await s.consistency();
+
+  const second = await bob.call("course_dna", "courses", "get_my_dashboard", {});
+  t.ok(second.Ok.last_seen);
+  t.equal(second.Ok.courses[0].module_count, 2);
+  t.equal(second.Ok.courses[0].content_count, 2);
+  t.deepEqual(second.Ok.courses[0].new_modules, [new_module.Ok]);
+  t.deepEqual(second.Ok.courses[0].new_contents, [new_content.Ok]);
+
+  const third = await bob.call("course_dna", "courses", "get_my_dashboard", {});
+  t.deepEqual(third.Ok.courses[0].new_modules, []);
+})
+
+orchestrator.registerScenario("Scenario32: Content moderation", async (s, t) => {
+  const { alice, bob } = await s.players(
+    {alice: conductorConfig, bob: conductorConfig},
+    true
+  );
+  const bob_addr = bob.instance("course_dna").agentAddress;
+
+  const course_addr = await create_course(alice, "reported course");
+  await alice.call("course_dna", "members", "grant_role", { agent_address: bob_addr, role: "Moderator" });
+  await s.consistency();
+
+  const no_reason = await bob.call("course_dna", "courses", "report", { address: course_addr.Ok, reason: "" });
+  t.ok(no_reason.Err);
+  const reported = await bob.call("course_dna", "courses", "report", { address: course_addr.Ok, reason: "spam" });
+  t.ok(reported.Ok);
+  await s.consistency();
+
+  const not_moderator = await alice.call("course_dna", "courses", "get_open_reports", {});
+  t.ok(not_moderator.Err);
+  const open = await bob.call("course_dna", "courses", "get_open_reports", {});
+  t.equal(open.Ok.length, 1);
+  t.equal(open.Ok[0].item_address, course_addr.Ok);
+  t.equal(open.Ok[0].reports[0].reason, "spam");
+
+  const forbidden = await alice.call("course_dna", "courses", "moderate", { address: course_addr.Ok, action: "Hide", reason: "spam" });
+  t.ok(forbidden.Err);
+  const hidden = await bob.call("course_dna", "courses", "moderate", { address: course_addr.Ok, action: "Hide", reason: "spam" });
+  t.ok(hidden.Ok);
+  await s.consistency();
+
+  const listed = await bob.call("course_dna", "courses", "get_all_courses", {});
+  t.notOk(listed.Ok.includes(course_addr.Ok));
+  const own = await alice.call("course_dna", "courses", "get_my_courses", {});
+  t.ok(own.Ok.includes(course_addr.Ok));
+  const taken_down = await bob.call("course_dna", "courses", "get_entry", { address: course_addr.Ok });
+  t.ok(taken_down.Err);
+  const teacher_copy = await alice.call("course_dna", "courses", "get_entry", { address: course_addr.Ok });
+  t.ok(teacher_copy.Ok);
+  const closed = await bob.call("course_dna", "courses", "get_open_reports", {});
+  t.equal(closed.Ok.length, 0);
+
+  const appealed = await alice.call("course_dna", "courses", "appeal", { address: course_addr.Ok, reason: "not spam" });
+  t.ok(appealed.Ok);
+  await s.consistency();
+  const appeals = await bob.call("course_dna", "courses", "get_open_reports", {});
+  t.equal(appeals.Ok[0].appeals[0].reason, "not spam");
+
+  await bob.call("course_dna", "courses", "moderate", { address: course_addr.Ok, action: "Restore", reason: "appeal upheld" });
+  await s.consistency();
+  const relisted = await bob.call("course_dna", "courses", "get_all_courses", {});
+  t.ok(relisted.Ok.includes(course_addr.Ok));
+
+  const trail = await alice.call("course_dna", "courses", "get_moderation_trail", { address: course_addr.Ok });
+  t.equal(trail.Ok.hidden, false);
+  t.equal(trail.Ok.reports.length, 1);
+  t.deepEqual(trail.Ok.decisions.map(d => d.action), ["Hide", "Restore"]);
+  t.equal(trail.Ok.appeals.length, 1);
+})
+
+// Mirrors bundle::content_hash: SHA-256 of the compact JSON of these three keys, in this order
+const content_hash = ({ name, url, description }) =>
+  crypto.createHash("sha256").update(JSON.stringify({ name, url, description })).digest("hex");
+
+orchestrator.registerScenario("Scenario33: Course export bundle", async (s, t) => {
+  const { alice, bob } = await s.players(
+    {alice: conductorConfig, bob: conductorConfig},
+    true
+  );
+  const alice_addr = alice.instance("course_dna").agentAddress;
+
+  const course_addr = await create_course(alice, "exported course");
+  const module_1 = await create_module(alice, "first module", course_addr.Ok);
+  const module_2 = await create_module(alice, "second module", course_addr.Ok);
+  await s.consistency();
+  const content_1 = await create_content(alice, "intro", module_1.Ok, "https://example.com/intro", "start here");
+  // Contents are ordered by creation time, which the chain records to the second
+  await new Promise(resolve => setTimeout(resolve, 1500));
+  const content_2 = await create_content(alice, "ünïcode \"quoted\"", module_1.Ok, "https://example.com/2", "line\nbreak");
+  await create_content(alice, "next", module_2.Ok, "https://example.com/next", "");
+  await s.consistency();
+
+  const not_teacher = await bob.call("course_dna", "courses", "export_course", { course_address: course_addr.Ok });
+  t.ok(not_teacher.Err);
+
+  const exported = await alice.call("course_dna", "courses", "export_course", { course_address: course_addr.Ok });
+  const bundle = exported.Ok;
+  t.equal(bundle.format, "hudemy.course");
+  t.equal(bundle.schema_version, 1);
+  t.equal(bundle.source.course_address, course_addr.Ok);
+  t.equal(bundle.source.teacher_address, alice_addr);
+  t.equal(bundle.course.title, "exported course");
+  t.deepEqual(bundle.modules.map(m => m.source_address), [module_1.Ok, module_2.Ok]);
+  t.deepEqual(bundle.modules[0].contents.map(c => c.source_address), [content_1.Ok, content_2.Ok]);
+  for (const module of bundle.modules) {
+    for (const content of module.contents) {
+      t.equal(content.hash, content_hash(content));
+    }
+  }
+
+  // The bundle survives being written out and read back as plain JSON
+  const round_trip = JSON.parse(JSON.stringify(bundle));
+  t.deepEqual(round_trip, bundle);
+  const again = await alice.call("course_dna", "courses", "export_course", { course_address: course_addr.Ok });
+  const { exported_at, ...stable } = again.Ok;
+  const { exported_at: _, ...stable_round_trip } = round_trip;
+  t.deepEqual(stable, stable_round_trip);
+})
+
+orchestrator.registerScenario("Scenario34: Course import round trip", async (s, t) => {
+  const { alice } = await s.players(
+    {alice: conductorConfig},
+    true
+  );
+
+  const course_addr = await create_course(alice, "original course");
+  const module_1 = await create_module(alice, "first module", course_addr.Ok);
+  const module_2 = await create_module(alice, "second module", course_addr.Ok);
+  await s.consistency();
+  await alice.call("course_dna", "courses", "update_module_release", {
+    module_address: module_2.Ok, release_offset: 86400, release_at: null
+  });
+  await create_content(alice, "intro", module_1.Ok, "https://example.com/intro", "start here");
+  await create_content(alice, "next", module_2.Ok, "https://example.com/next", "keep going");
+  await s.consistency();
+
+  const exported = await alice.call("course_dna", "courses", "export_course", { course_address: course_addr.Ok });
+  const bundle = JSON.parse(JSON.stringify(exported.Ok));
+  const courses_before = await alice.call("course_dna", "courses", "get_my_courses", {});
+
+  const long_title = await alice.call("course_dna", "courses", "import_course", {
+    bundle: { ...bundle, course: { ...bundle.course, title: "x".repeat(51) } }
+  });
+  t.ok(long_title.Err);
+  const tampered = JSON.parse(JSON.stringify(bundle));
+  tampered.modules[0].contents[0].url = "https://example.com/elsewhere";
+  const bad_hash = await alice.call("course_dna", "courses", "import_course", { bundle: tampered });
+  t.ok(bad_hash.Err);
+  const newer = await alice.call("course_dna", "courses", "import_course", {
+    bundle: { ...bundle, schema_version: 2 }
+  });
+  t.ok(newer.Err);
+  await s.consistency();
+  const courses_after_errors = await alice.call("course_dna", "courses", "get_my_courses", {});
+  t.equal(courses_after_errors.Ok.length, courses_before.Ok.length);
+
+  const imported = await alice.call("course_dna", "courses", "import_course", { bundle });
+  t.ok(imported.Ok);
+  t.deepEqual(imported.Ok.modules.map(m => m.source_address), [module_1.Ok, module_2.Ok]);
+  t.equal(imported.Ok.contents.length, 2);
+  await s.consistency();
+
+  const reexported = await alice.call("course_dna", "courses", "export_course", { course_address: imported.Ok.course_address });
+  const comparable = b => ({
+    title: b.course.title,
+    modules: b.modules.map(({ source_address, contents, ...module }) => ({
+      ...module,
+      contents: contents.map(({ source_address, ...content }) => content)
+    }))
+  });
+  t.deepEqual(comparable(reexported.Ok), comparable(bundle));
+  t.deepEqual(reexported.Ok.modules.map(m => m.source_address), imported.Ok.modules.map(m => m.address));
+})
+
+const cartridge_manifest = `<?xml version="1.0" encoding="UTF-8"?>
+<manifest identifier="cc_1" xmlns="http://www.imsglobal.org/xsd/imsccv1p1/imscp_v1p1" xmlns:lomimscc="http://ltsc.ieee.org/xsd/imsccv1p1/LOM/manifest">
+  <metadata>
+    <schema>IMS Common Cartridge</schema>
+    <lomimscc:lom><lomimscc:general><lomimscc:title><lomimscc:string language="en">Rust &amp; You</lomimscc:string></lomimscc:title></lomimscc:general></lomimscc:lom>
+  </metadata>
+  <organizations>
+    <organization identifier="org_1" structure="rooted-hierarchy">
+      <item identifier="root">
+        <item identifier="week1"><title>Week 1</title>
+          <item identifier="i1" identifierref="r1"><title>Intro page</title></item>
+          <item identifier="folder"><title>Reading</title>
+            <item identifier="i2" identifierref="r2"><title>Docs</title></item>
+            <item identifier="i4" identifierref="r4"><title>Book</title></item>
+          </item>
+        </item>
+        <item identifier="i3" identifierref="r3"><title>Quiz</title></item>
+      </item>
+    </organization>
+  </organizations>
+  <resources>
+    <resource identifier="r1" type="webcontent" href="web/intro.html"><file href="web/intro.html"/></resource>
+    <resource identifier="r2" type="imswl_xmlv1p1"><file href="r2.xml"/></resource>
+    <resource identifier="r3" type="imsqti_xmlv1p2/imscc_xmlv1p1/assessment"><file href="r3.xml"/></resource>
+    <resource identifier="r4" type="imswl_xmlv1p1"><webLink><title>Book</title><url href="https://doc.rust-lang.org/book/"/></webLink></resource>
+  </resources>
+</manifest>`;
+
+orchestrator.registerScenario("Scenario35: Common Cartridge conversion", async (s, t) => {
+  const { alice } = await s.players(
+    {alice: conductorConfig},
+    true
+  );
+
+  const not_xml = await alice.call("course_dna", "courses", "convert_cartridge", { manifest: "<manifest><organizations></manifest>" });
+  t.ok(not_xml.Err);
+  const too_deep = await alice.call("course_dna", "courses", "convert_cartridge", { manifest: "<a>".repeat(1000) + "</a>".repeat(1000) });
+  t.ok(too_deep.Err);
+
+  const converted = await alice.call("course_dna", "courses", "convert_cartridge", { manifest: cartridge_manifest });
+  const bundle = converted.Ok.bundle;
+  t.equal(bundle.course.title, "Rust & You");
+  t.deepEqual(bundle.modules.map(m => m.title), ["Week 1"]);
+  t.deepEqual(bundle.modules[0].contents.map(c => [c.name, c.url]), [
+    ["Intro page", "web/intro.html"],
+    ["Book", "https://doc.rust-lang.org/book/"]
+  ]);
+  t.deepEqual(converted.Ok.unsupported, [{
+    item_identifier: "i2",
+    item_title: "Docs",
+    resource_identifier: "r2",
+    resource_type: "imswl_xmlv1p1"
+  }, {
+    item_identifier: "i3",
+    item_title: "Quiz",
+    resource_identifier: "r3",
+    resource_type: "imsqti_xmlv1p2/imscc_xmlv1p1/assessment"
+  }]);
+
+  const imported = await alice.call("course_dna", "courses", "import_course", { bundle });
+  t.ok(imported.Ok);
+  t.deepEqual(imported.Ok.contents.map(c => c.source_address), ["i1", "i4"]);
+})
+
+orchestrator.registerScenario("Scenario36: Clone a course", async (s, t) => {
+  const { alice, bob } = await s.players(
+    {alice: conductorConfig, bob: conductorConfig},
+    true
+  );
+  const bob_addr = bob.instance("course_dna").agentAddress;
+
+  const source_addr = await create_course(alice, "source course");
+  const module_addr = await create_module(alice, "only module", source_addr.Ok);
+  await s.consistency();
+  const content_addr = await create_content(alice, "lesson", module_addr.Ok, "https://example.com/lesson", "read me");
+  await s.consistency();
+  const later_addr = await create_module(alice, "later module", source_addr.Ok);
+  await s.consistency();
+  await alice.call("course_dna", "courses", "update_module_release", {
+    module_address: later_addr.Ok, release_offset: null, release_at: 4102444800
+  });
+  await s.consistency();
+
+  const too_long = await bob.call("course_dna", "courses", "clone_course", { source_address: source_addr.Ok, new_title: "x".repeat(51) });
+  t.ok(too_long.Err);
+
+  const cloned = await bob.call("course_dna", "courses", "clone_course", { source_address: source_addr.Ok, new_title: "bob's copy" });
+  t.ok(cloned.Ok);
+  t.deepEqual(cloned.Ok.modules.map(m => m.source_address), [module_addr.Ok]);
+  t.deepEqual(cloned.Ok.contents.map(c => c.source_address), [content_addr.Ok]);
+  await s.consistency();
+
+  const forked_from = await alice.call("course_dna", "courses", "get_forked_from", { course_address: cloned.Ok.course_address });
+  t.equal(forked_from.Ok, source_addr.Ok);
+  const listed = await alice.call("course_dna", "courses", "get_all_courses", {});
+  t.ok(listed.Ok.includes(cloned.Ok.course_address));
+
+  const copy = await bob.call("course_dna", "courses", "export_course", { course_address: cloned.Ok.course_address });
+  t.equal(copy.Ok.source.teacher_address, bob_addr);
+  t.equal(copy.Ok.course.title, "bob's copy");
+  t.deepEqual(copy.Ok.modules[0].contents.map(({ source_address, ...content }) => content), [{
+    name: "lesson",
+    url: "https://example.com/lesson",
+    description: "read me",
+    hash: content_hash({ name: "lesson", url: "https://example.com/lesson", description: "read me" })
+  }]);
+
+  await alice.call("course_dna", "courses", "update_course_forking", { course_address: source_addr.Ok, forkable: false });
+  await s.consistency();
+  const refused = await bob.call("course_dna", "courses", "clone_course", { source_address: source_addr.Ok, new_title: "another copy" });
+  t.ok(refused.Err);
+  const own = await alice.call("course_dna", "courses", "clone_course", { source_address: source_addr.Ok, new_title: "alice's copy" });
+  t.ok(own.Ok);
+
+  // a clone of a private course is private too and only publishes sealed copies
+  await alice.call("course_dna", "courses", "update_course_visibility", { course_address: source_addr.Ok, private: true });
+  await s.consistency();
+  const private_copy = await alice.call("course_dna", "courses", "clone_course", { source_address: source_addr.Ok, new_title: "private copy" });
+  t.ok(private_copy.Ok);
+  await s.consistency();
+  const sealed = await bob.call("course_dna", "courses", "get_entry", { address: private_copy.Ok.contents[0].address });
+  t.false(JSON.stringify(sealed).includes("https://example.com/lesson"));
+  const private_course = await bob.call("course_dna", "courses", "get_entry", { address: private_copy.Ok.course_address });
+  t.true(JSON.parse(private_course.Ok.App[1]).private);
+})
+
+orchestrator.registerScenario("Scenario37: Learning paths", async (s, t) => {
+  const { alice, bob } = await s.players(
+    {alice: conductorConfig, bob: conductorConfig},
+    true
+  );
+  const bob_addr = bob.instance("course_dna").agentAddress;
+
+  const first_addr = await create_course(alice, "first course");
+  const second_addr = await create_course(alice, "second course");
+  await s.consistency();
+
+  const empty = await alice.call("course_dna", "courses", "create_path", { title: "empty", courses: [] });
+  t.ok(empty.Err);
+
+  const path_addr = await alice.call("course_dna", "courses", "create_path", {
+    title: "rust track",
+    courses: [first_addr.Ok]
+  });
+  t.ok(path_addr.Ok);
+  await s.consistency();
+
+  const paths = await bob.call("course_dna", "courses", "list_paths", {});
+  t.deepEqual(paths.Ok, [path_addr.Ok]);
+
+  const duplicate = await alice.call("course_dna", "courses", "update_path", {
+    path_address: path_addr.Ok,
+    title: "rust track",
+    courses: [first_addr.Ok, first_addr.Ok]
+  });
+  t.ok(duplicate.Err);
+  const not_curator = await bob.call("course_dna", "courses", "update_path", {
+    path_address: path_addr.Ok,
+    title: "bob's track",
+    courses: [second_addr.Ok]
+  });
+  t.ok(not_curator.Err);
+  const updated = await alice.call("course_dna", "courses", "update_path", {
+    path_address: path_addr.Ok,
+    title: "rust track",
+    courses: [first_addr.Ok, second_addr.Ok]
+  });
+  t.ok(updated.Ok);
+  await s.consistency();
+
+  // courses that run cohorts need one chosen for them
+  const term = await alice.call("course_dna", "courses", "create_cohort", {
+    course_address: second_addr.Ok, title: "term", start_date: 4102444800, end_date: 4118083200, max_students: null
+  });
+  await s.consistency();
+
+  const enrollment = await bob.call("course_dna", "courses", "enrol_in_path", { path_address: path_addr.Ok, cohorts: [] });
+  t.deepEqual(enrollment.Ok.enrolled, [first_addr.Ok]);
+  t.deepEqual(enrollment.Ok.skipped.map(c => c.course_address), [second_addr.Ok]);
+  await s.consistency();
+
+  const with_cohort = await bob.call("course_dna", "courses", "enrol_in_path", {
+    path_address: path_addr.Ok,
+    cohorts: [{ course_address: second_addr.Ok, cohort_address: term.Ok }]
+  });
+  t.deepEqual(with_cohort.Ok, { enrolled: [second_addr.Ok], skipped: [] });
+  await s.consistency();
+
+  const my_paths = await bob.call("course_dna", "courses", "get_my_paths", {});
+  t.deepEqual(my_paths.Ok, [path_addr.Ok]);
+  const enrolled = await bob.call("course_dna", "courses", "get_my_enrolled_courses", {});
+  t.ok(enrolled.Ok.includes(first_addr.Ok));
+  t.ok(enrolled.Ok.includes(second_addr.Ok));
+
+  const again = await bob.call("course_dna", "courses", "enrol_in_path", { path_address: path_addr.Ok, cohorts: [] });
+  t.deepEqual(again.Ok, { enrolled: [], skipped: [] });
+
+  await alice.call("course_dna", "courses", "issue_completion", { course_address: first_addr.Ok, student_address: bob_addr });
+  await s.consistency();
+  const halfway = await bob.call("course_dna", "courses", "get_path", { path_address: path_addr.Ok });
+  t.equal(halfway.Ok.title, "rust track");
+  t.deepEqual(halfway.Ok.courses.map(c => [c.title, c.completed]), [
+    ["first course", true],
+    ["second course", false]
+  ]);
+  t.equal(halfway.Ok.completed, false);
+
+  await alice.call("course_dna", "courses", "issue_completion", { course_address: second_addr.Ok, student_address: bob_addr });
+  await s.consistency();
+  const finished = await bob.call("course_dna", "courses", "get_path", { path_address: path_addr.Ok });
+  t.equal(finished.Ok.completed, true);
+})
+
 orchestrator.run();
\ No newline at end of file
@@ -0,0 +1,54 @@
+/************************ Import Required Libraries */
+use hdk::prelude::*;
+
+use crate::content::Content;
+use crate::course::Course;
+use crate::moderation;
+use crate::module::Module;
+use crate::schedule;
+use hdk::AGENT_ADDRESS;
+use std::convert::TryFrom;
+/******************************************* */
+
+/// Helper Functions
+
+/// Serves raw entries with the same rules as the listings: students get
+/// courses without their unreleased or hidden modules, and no unreleased
+/// module or content, nor anything a moderator took down.
+pub fn get_entry(address: Address) -> ZomeApiResult<Option<Entry>> {
+    let entry = match hdk::get_entry(&address)? {
+        Some(entry) => entry,
+        None => return Ok(None),
+    };
+    let (entry_type, value) = match &entry {
+        Entry::App(entry_type, value) => (entry_type.to_string(), value.clone()),
+        _ => return Ok(Some(entry)),
+    };
+
+    match entry_type.as_str() {
+        "course" => {
+            let mut course = Course::try_from(value)?;
+            moderation::validate_visible(&course, &AGENT_ADDRESS, &[&address])?;
+            if course.teacher_address != Address::from(AGENT_ADDRESS.to_string()) {
+                course.modules = moderation::filter_hidden(schedule::released_modules(&course, &AGENT_ADDRESS)?)?;
+            }
+            Ok(Some(course.entry()))
+        }
+        "module" => {
+            let module = Module::try_from(value)?;
+            let course: Course = hdk::utils::get_as_type(module.course_address.clone())?;
+            moderation::validate_visible(&course, &AGENT_ADDRESS, &[&module.course_address, &address])?;
+            schedule::validate_released(&module, &AGENT_ADDRESS)?;
+            Ok(Some(entry))
+        }
+        "content" => {
+            let content = Content::try_from(value)?;
+            let module: Module = hdk::utils::get_as_type(content.module_address().clone())?;
+            let course: Course = hdk::utils::get_as_type(module.course_address.clone())?;
+            moderation::validate_visible(&course, &AGENT_ADDRESS, &[&module.course_address, content.module_address(), &address])?;
+            schedule::validate_released(&module, &AGENT_ADDRESS)?;
+            Ok(Some(entry))
+        }
+        _ => Ok(Some(entry)),
+    }
+}
@@ -0,0 +1,214 @@
+/************************ Import Required Libraries */
+use hdk::prelude::*;
+
+use crate::content;
+use crate::course;
+use crate::course::Course;
+use chrono::{DateTime, FixedOffset, TimeZone, Utc};
+use hdk::AGENT_ADDRESS;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+/******************************************* */
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Committed by a student the first time they open a content.
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct ContentView {
+    pub content_address: Address,
+    pub student_address: Address,
+}
+
+impl ContentView {
+    pub fn new(content_address: Address, student_address: Address) -> Self {
+        ContentView {
+            content_address,
+            student_address,
+        }
+    }
+
+    pub fn entry(&self) -> Entry {
+        Entry::App("content_view".into(), self.into())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct EnrollmentBucket {
+    pub period_start: String, // YYYY-MM-DD, weeks start on Monday
+    pub count: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct ContentAnalytics {
+    pub content_address: Address,
+    pub module_address: Address,
+    pub views: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct CourseAnalytics {
+    pub course_address: Address,
+    pub total_students: u32,
+    pub enrollments_by_day: Vec<EnrollmentBucket>,
+    pub enrollments_by_week: Vec<EnrollmentBucket>,
+    pub contents: Vec<ContentAnalytics>, // In course order
+    pub drop_off: Option<Address>, // The content most students stopped at, if any stopped early
+}
+
+////////////////////Content View Entry Definition
+pub fn entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "content_view",
+        description: "a record that a student has opened a content",
+        sharing: Sharing::Public,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: | validation_data: hdk::EntryValidationData<ContentView> | {
+            match validation_data {
+                EntryValidationData::Create {entry, validation_data} => {
+                    if !validation_data.sources().contains(&entry.student_address) {
+                        return Err(String::from("Only the student can record their own views"));
+                    }
+                    Ok(())
+                },
+                _ => Err(String::from("Content views cannot be modified or deleted"))
+            }
+        },
+        links: [
+            from!(
+                "content",
+                link_type: "content->views",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | _validation_data: hdk::LinkValidationData | {
+                    Ok(())
+                }
+            )
+        ]
+    )
+}
+
+/// Helper Functions
+/// Only enrolled students' views count.
+pub fn record_view(content_address: &Address, course_address: &Address, course: &Course) -> ZomeApiResult<()> {
+    let student_address: Address = AGENT_ADDRESS.to_string().into();
+    if course.teacher_address == student_address || !course::get_students(course_address.clone())?.contains(&student_address) {
+        return Ok(());
+    }
+    let view = ContentView::new(content_address.clone(), student_address);
+    let view_address = hdk::entry_address(&view.entry())?;
+    if hdk::get_entry(&view_address)?.is_none() {
+        hdk::commit_entry(&view.entry())?;
+        hdk::link_entries(content_address, &view_address, "content->views", "")?;
+    }
+    Ok(())
+}
+
+fn get_viewers(content_address: &Address) -> ZomeApiResult<Vec<Address>> {
+    let views: Vec<ContentView> = hdk::utils::get_links_and_load_type(
+        content_address,
+        LinkMatch::Exactly("content->views"),
+        LinkMatch::Any,
+    )?;
+    let mut viewers: Vec<Address> = views.into_iter().map(|view| view.student_address).collect();
+    viewers.sort_by_key(|address| address.to_string());
+    viewers.dedup();
+    Ok(viewers)
+}
+
+fn enrollment_days(course_address: &Address) -> ZomeApiResult<Vec<i64>> {
+    let links = hdk::get_links_with_options(
+        course_address,
+        LinkMatch::Exactly("course->students"),
+        LinkMatch::Any,
+        GetLinksOptions {
+            headers: true,
+            ..GetLinksOptions::default()
+        },
+    )?;
+    Ok(links
+        .links()
+        .iter()
+        .filter_map(|link| link.headers.first())
+        .map(|header| DateTime::<FixedOffset>::from(header.timestamp()).timestamp() / SECONDS_PER_DAY)
+        .collect())
+}
+
+/// Counts enrollments per bucket, where `bucket_of` maps a day number
+/// (days since 1970-01-01) to the first day of its bucket.
+fn bucket(days: &[i64], bucket_of: impl Fn(i64) -> i64) -> Vec<EnrollmentBucket> {
+    let mut counts: BTreeMap<i64, u32> = BTreeMap::new();
+    for day in days {
+        *counts.entry(bucket_of(*day)).or_insert(0) += 1;
+    }
+    counts
+        .into_iter()
+        .map(|(day, count)| EnrollmentBucket {
+            period_start: Utc.timestamp(day * SECONDS_PER_DAY, 0).format("%Y-%m-%d").to_string(),
+            count,
+        })
+        .collect()
+}
+
+/// 1970-01-01 was a Thursday, so day + 3 counts days since a Monday.
+fn week_start(day: i64) -> i64 {
+    day - (day + 3) % 7
+}
+
+/// For each student, the furthest content they opened decides where they
+/// stopped. Students who reached the last content did not drop off.
+fn drop_off(contents: &[(Address, Vec<Address>)]) -> Option<Address> {
+    let last = contents.len().checked_sub(1)?;
+    let mut furthest: BTreeMap<String, usize> = BTreeMap::new();
+    for (position, (_, viewers)) in contents.iter().enumerate() {
+        for viewer in viewers {
+            furthest.insert(viewer.to_string(), position);
+        }
+    }
+
+    let mut stops = vec![0u32; contents.len()];
+    for position in furthest.values().filter(|position| **position < last) {
+        stops[*position] += 1;
+    }
+    let (position, count) = stops.iter().enumerate().max_by_key(|(position, count)| (**count, std::cmp::Reverse(*position)))?;
+    if *count == 0 {
+        return None;
+    }
+    Some(contents[position].0.clone())
+}
+
+pub fn get_course_analytics(course_address: Address) -> ZomeApiResult<CourseAnalytics> {
+    let course: Course = hdk::utils::get_as_type(course_address.clone())?;
+    if course.teacher_address != Address::from(AGENT_ADDRESS.to_string()) {
+        return Err(ZomeApiError::from(String::from(
+            "Only the teacher can see the analytics of a course",
+        )));
+    }
+
+    let days = enrollment_days(&course_address)?;
+
+    let mut viewed = Vec::new();
+    let mut contents = Vec::new();
+    for module_address in &course.modules {
+        for content_address in content::get_contents(module_address)? {
+            let viewers = get_viewers(&content_address)?;
+            contents.push(ContentAnalytics {
+                content_address: content_address.clone(),
+                module_address: module_address.clone(),
+                views: viewers.len() as u32,
+            });
+            viewed.push((content_address, viewers));
+        }
+    }
+
+    Ok(CourseAnalytics {
+        course_address: course_address.clone(),
+        total_students: course::get_students(course_address)?.len() as u32,
+        enrollments_by_day: bucket(&days, |day| day),
+        enrollments_by_week: bucket(&days, week_start),
+        contents,
+        drop_off: drop_off(&viewed),
+    })
+}
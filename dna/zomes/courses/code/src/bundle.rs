@@ -0,0 +1,267 @@
+/************************ Import Required Libraries */
+use hdk::prelude::*;
+
+use crate::clock;
+use crate::content;
+use crate::content::Content;
+use crate::course;
+use crate::course::Course;
+use crate::encryption;
+use crate::enrollment::EnrollmentPolicy;
+use crate::invite;
+use crate::module;
+use crate::module::Module;
+use crate::schedule;
+use hdk::holochain_core_types::time::Iso8601;
+use hdk::{AGENT_ADDRESS, DNA_ADDRESS};
+use sha2::{Digest, Sha256};
+use std::convert::TryFrom;
+/******************************************* */
+
+pub const BUNDLE_FORMAT: &str = "hudemy.course";
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// A course exported as a plain JSON document, to be archived or diffed
+/// outside Holochain.
+///
+/// Schema version 1:
+/// - `format` is always `"hudemy.course"` and `schema_version` is `1`.
+///   Readers should refuse other formats and versions they do not know.
+/// - `source` records where the course came from. Addresses are kept for
+///   reference only and mean nothing on another network.
+/// - `course` holds the course settings. Student data (enrollments, grades,
+///   payments) is never exported.
+/// - `modules` are in course order, and each module's `contents` in the order
+///   they were added.
+/// - Contents are exported decrypted, so bundles of encrypted courses must be
+///   stored as carefully as the course key.
+/// - Each content's `hash` is the lowercase hex SHA-256 of the compact JSON
+///   `{"name":…,"url":…,"description":…}`, with the keys in that order.
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct CourseBundle {
+    pub format: String,
+    pub schema_version: u32,
+    pub exported_at: Iso8601,
+    pub source: BundleSource,
+    pub course: CourseRecord,
+    pub modules: Vec<ModuleRecord>,
+}
+
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct BundleSource {
+    pub dna_address: Address,
+    pub course_address: Address,
+    pub teacher_address: Address,
+}
+
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct CourseRecord {
+    pub title: String,
+    pub created_at: Iso8601,
+    pub updated_at: Iso8601,
+    pub prerequisites: Vec<Address>, // Source addresses
+    pub max_students: Option<u32>,
+    pub enrollment_policy: EnrollmentPolicy,
+    pub private: bool,
+    pub encrypted: bool,
+    pub price: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct ModuleRecord {
+    pub source_address: Address,
+    pub title: String,
+    pub release_offset: Option<u64>,
+    pub release_at: Option<u64>,
+    pub contents: Vec<ContentRecord>,
+}
+
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct ContentRecord {
+    pub source_address: Address,
+    pub name: String,
+    pub url: String,
+    pub description: String,
+    pub hash: String,
+}
+
+/// Where an entry from the bundle ended up.
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct IdMapping {
+    pub source_address: Address,
+    pub address: Address,
+}
+
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct ImportReport {
+    pub course_address: Address,
+    pub modules: Vec<IdMapping>,
+    pub contents: Vec<IdMapping>,
+}
+
+/*********************** Bundle Validations */
+
+/// Checks everything an import would commit, so a bad bundle fails before
+/// anything is written.
+fn validate_bundle(bundle: &CourseBundle) -> ZomeApiResult<()> {
+    if bundle.format != BUNDLE_FORMAT {
+        return Err(ZomeApiError::from(String::from("This is not a course bundle")));
+    }
+    if bundle.schema_version != SCHEMA_VERSION {
+        return Err(ZomeApiError::from(format!(
+            "Unsupported bundle schema version {}",
+            bundle.schema_version
+        )));
+    }
+    course::validate_course_title(&bundle.course.title).map_err(ZomeApiError::from)?;
+
+    for (position, module) in bundle.modules.iter().enumerate() {
+        module::validate_module_title(&module.title)
+            .map_err(|error| ZomeApiError::from(format!("Module {}: {}", position + 1, error)))?;
+        schedule::validate_release_times(&module.release_offset, &module.release_at)
+            .map_err(|error| ZomeApiError::from(format!("Module {}: {}", position + 1, error)))?;
+        for content in &module.contents {
+            if content.hash != content_hash(&content.name, &content.url, &content.description) {
+                return Err(ZomeApiError::from(format!(
+                    "Content {} does not match its hash",
+                    content.name
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Helper Functions
+pub fn content_hash(name: &str, url: &str, description: &str) -> String {
+    let hashed = json!({"name": name, "url": url, "description": description}).to_string();
+    Sha256::digest(hashed.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Puts decrypted contents in the order they were added.
+pub fn content_records(mut contents: Vec<(Address, Content)>) -> Vec<ContentRecord> {
+    contents.sort_by_key(|(content_address, content)| (content.created_at().clone(), content_address.to_string()));
+    contents
+        .into_iter()
+        .map(|(source_address, content)| ContentRecord {
+            source_address,
+            name: content.name().to_string(),
+            url: content.url().to_string(),
+            description: content.description().to_string(),
+            hash: content_hash(content.name(), content.url(), content.description()),
+        })
+        .collect()
+}
+
+fn export_contents(module_address: &Address) -> ZomeApiResult<Vec<ContentRecord>> {
+    let mut contents = Vec::new();
+    for content_address in content::get_contents(module_address)? {
+        // Read through content::read so encrypted contents come back decrypted
+        let content: Content = content::read(content_address.clone())?;
+        contents.push((content_address, content));
+    }
+    Ok(content_records(contents))
+}
+
+pub fn export_course(course_address: Address) -> ZomeApiResult<CourseBundle> {
+    let course: Course = hdk::utils::get_as_type(course_address.clone())?;
+    if course.teacher_address != Address::from(AGENT_ADDRESS.to_string()) {
+        return Err(ZomeApiError::from(String::from(
+            "Only the teacher can export a course",
+        )));
+    }
+
+    let mut modules = Vec::new();
+    for module_address in &course.modules {
+        let module: Module = hdk::utils::get_as_type(module_address.clone())?;
+        modules.push(ModuleRecord {
+            source_address: module_address.clone(),
+            title: module.title,
+            release_offset: module.release_offset,
+            release_at: module.release_at,
+            contents: export_contents(module_address)?,
+        });
+    }
+
+    Ok(CourseBundle {
+        format: String::from(BUNDLE_FORMAT),
+        schema_version: SCHEMA_VERSION,
+        exported_at: clock::now()?,
+        source: BundleSource {
+            dna_address: DNA_ADDRESS.to_string().into(),
+            course_address,
+            teacher_address: course.teacher_address.clone(),
+        },
+        course: CourseRecord {
+            title: course.title,
+            created_at: course.created_at,
+            updated_at: course.updated_at,
+            prerequisites: course.prerequisites,
+            max_students: course.max_students,
+            enrollment_policy: course.enrollment_policy,
+            private: course.private,
+            encrypted: course.content_key_version.is_some(),
+            price: course.price,
+        },
+        modules,
+    })
+}
+
+/// Creates a new course owned by the caller from a bundle. Only the course
+/// title, modules and contents are imported; settings such as the price,
+/// prerequisites or seat limit are left for the new teacher to set.
+pub fn import_course(bundle: CourseBundle) -> ZomeApiResult<ImportReport> {
+    validate_bundle(&bundle)?;
+    course::validate_teacher_role(&AGENT_ADDRESS)?;
+    create_course_from(bundle.course.title, bundle.modules, false, false)
+}
+
+/// Commits a new course with the given modules and contents, in order. A
+/// `private` or `encrypted` course is made so before anything is added to it,
+/// so its contents are only ever published sealed.
+pub fn create_course_from(
+    title: String,
+    module_records: Vec<ModuleRecord>,
+    private: bool,
+    encrypted: bool,
+) -> ZomeApiResult<ImportReport> {
+    let course_address = course::create(title)?;
+    if private {
+        invite::update_visibility(course_address.clone(), true)?;
+    } else if encrypted {
+        encryption::update_encryption(course_address.clone(), true)?;
+    }
+    let mut report = ImportReport {
+        course_address: course_address.clone(),
+        modules: Vec::new(),
+        contents: Vec::new(),
+    };
+
+    for module_record in module_records {
+        let module_address = module::create(module_record.title, &course_address)?;
+        if module_record.release_offset.is_some() || module_record.release_at.is_some() {
+            schedule::update_release(module_address.clone(), module_record.release_offset, module_record.release_at)?;
+        }
+        report.modules.push(IdMapping {
+            source_address: module_record.source_address,
+            address: module_address.clone(),
+        });
+
+        for content_record in module_record.contents {
+            let content_address = content::create(
+                content_record.name,
+                module_address.clone(),
+                content_record.url,
+                content_record.description,
+            )?;
+            report.contents.push(IdMapping {
+                source_address: content_record.source_address,
+                address: content_address,
+            });
+        }
+    }
+    Ok(report)
+}
@@ -0,0 +1,441 @@
+/************************ Import Required Libraries */
+use hdk::prelude::*;
+
+use crate::bundle;
+use crate::bundle::{BundleSource, ContentRecord, CourseBundle, CourseRecord, ModuleRecord};
+use crate::clock;
+use crate::enrollment::EnrollmentPolicy;
+use hdk::{AGENT_ADDRESS, DNA_ADDRESS};
+use std::convert::TryFrom;
+/******************************************* */
+
+const WEB_CONTENT: &str = "webcontent";
+const WEB_LINK_PREFIX: &str = "imswl_xmlv1p"; // imswl_xmlv1p1, imswl_xmlv1p2...
+const MAX_DEPTH: usize = 64; // Real manifests nest a handful of levels
+
+/// A manifest item that could not become a content.
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct UnsupportedResource {
+    pub item_identifier: String,
+    pub item_title: String,
+    pub resource_identifier: String,
+    pub resource_type: String, // "missing" when the item points to no resource
+}
+
+/// The course found in a cartridge, ready for `import_course`, and what had
+/// to be left out of it.
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct CartridgeConversion {
+    pub bundle: CourseBundle,
+    pub unsupported: Vec<UnsupportedResource>,
+}
+
+/*********************** Manifest Parsing */
+
+/// Just enough XML for a manifest: elements, attributes and text.
+/// Namespace prefixes are dropped, and comments, processing instructions
+/// and doctypes are skipped.
+#[derive(Debug)]
+struct XmlElement {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<XmlNode>,
+}
+
+#[derive(Debug)]
+enum XmlNode {
+    Element(XmlElement),
+    Text(String),
+}
+
+impl XmlElement {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(attribute, _)| attribute == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn elements(&self) -> impl Iterator<Item = &XmlElement> {
+        self.children.iter().filter_map(|child| match child {
+            XmlNode::Element(element) => Some(element),
+            XmlNode::Text(_) => None,
+        })
+    }
+
+    fn child(&self, name: &str) -> Option<&XmlElement> {
+        self.elements().find(|element| element.name == name)
+    }
+
+    fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a XmlElement> {
+        self.elements().filter(move |element| element.name == name)
+    }
+
+    fn descendant(&self, name: &str) -> Option<&XmlElement> {
+        for element in self.elements() {
+            if element.name == name {
+                return Some(element);
+            }
+            if let Some(found) = element.descendant(name) {
+                return Some(found);
+            }
+        }
+        None
+    }
+
+    fn text(&self) -> String {
+        let text: String = self
+            .children
+            .iter()
+            .filter_map(|child| match child {
+                XmlNode::Text(text) => Some(text.as_str()),
+                XmlNode::Element(_) => None,
+            })
+            .collect();
+        text.trim().to_string()
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.text[self.position..]
+    }
+
+    fn error(&self, message: &str) -> ZomeApiError {
+        ZomeApiError::from(format!("Invalid manifest at byte {}: {}", self.position, message))
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    /// Moves past the next `end`, returning what came before it.
+    fn take_until(&mut self, end: &str) -> ZomeApiResult<&'a str> {
+        let rest = self.rest();
+        match rest.find(end) {
+            Some(index) => {
+                self.position += index + end.len();
+                Ok(&rest[..index])
+            }
+            None => Err(self.error(&format!("expected {}", end))),
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> ZomeApiResult<()> {
+        if !self.rest().starts_with(token) {
+            return Err(self.error(&format!("expected {}", token)));
+        }
+        self.position += token.len();
+        Ok(())
+    }
+
+    /// Skips a comment, processing instruction or doctype if one starts here.
+    fn skip_markup(&mut self) -> ZomeApiResult<bool> {
+        let rest = self.rest();
+        if rest.starts_with("<!--") {
+            self.take_until("-->")?;
+        } else if rest.starts_with("<?") {
+            self.take_until("?>")?;
+        } else if rest.starts_with("<!") && !rest.starts_with("<![CDATA[") {
+            self.take_until(">")?;
+        } else {
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    fn read_name(&mut self) -> ZomeApiResult<String> {
+        let rest = self.rest();
+        let length = rest
+            .find(|c: char| c.is_whitespace() || c == '/' || c == '>' || c == '=')
+            .unwrap_or_else(|| rest.len());
+        if length == 0 {
+            return Err(self.error("expected a name"));
+        }
+        self.position += length;
+        Ok(local_name(&rest[..length]))
+    }
+
+    fn parse_document(&mut self) -> ZomeApiResult<XmlElement> {
+        loop {
+            self.skip_whitespace();
+            if !self.skip_markup()? {
+                return self.parse_element(0);
+            }
+        }
+    }
+
+    fn parse_element(&mut self, depth: usize) -> ZomeApiResult<XmlElement> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("elements are nested too deeply"));
+        }
+        self.expect("<")?;
+        let mut element = XmlElement {
+            name: self.read_name()?,
+            attributes: Vec::new(),
+            children: Vec::new(),
+        };
+
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("/>") {
+                self.position += 2;
+                return Ok(element);
+            }
+            if self.rest().starts_with('>') {
+                self.position += 1;
+                break;
+            }
+            let name = self.read_name()?;
+            self.skip_whitespace();
+            self.expect("=")?;
+            self.skip_whitespace();
+            let quote = if self.rest().starts_with('\'') { "'" } else { "\"" };
+            self.expect(quote)?;
+            let value = decode_entities(self.take_until(quote)?);
+            element.attributes.push((name, value));
+        }
+
+        loop {
+            let rest = self.rest();
+            if rest.is_empty() {
+                return Err(self.error(&format!("<{}> is not closed", element.name)));
+            } else if rest.starts_with("</") {
+                self.position += 2;
+                let name = self.read_name()?;
+                if name != element.name {
+                    return Err(self.error(&format!("<{}> is closed by </{}>", element.name, name)));
+                }
+                self.take_until(">")?;
+                return Ok(element);
+            } else if rest.starts_with("<![CDATA[") {
+                self.position += "<![CDATA[".len();
+                let text = self.take_until("]]>")?;
+                element.children.push(XmlNode::Text(text.to_string()));
+            } else if self.skip_markup()? {
+                continue;
+            } else if rest.starts_with('<') {
+                element.children.push(XmlNode::Element(self.parse_element(depth + 1)?));
+            } else {
+                let length = rest.find('<').unwrap_or_else(|| rest.len());
+                self.position += length;
+                element.children.push(XmlNode::Text(decode_entities(&rest[..length])));
+            }
+        }
+    }
+}
+
+fn local_name(name: &str) -> String {
+    name.rsplit(':').next().unwrap_or(name).to_string()
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = match rest.find(';') {
+            Some(end) => end,
+            None => break,
+        };
+        let entity = &rest[1..end];
+        let character = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ if entity.starts_with("#x") => u32::from_str_radix(&entity[2..], 16).ok().and_then(std::char::from_u32),
+            _ if entity.starts_with('#') => entity[1..].parse().ok().and_then(std::char::from_u32),
+            _ => None,
+        };
+        match character {
+            Some(character) => {
+                decoded.push(character);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Helper Functions
+fn item_title(item: &XmlElement) -> String {
+    item.child("title")
+        .map(|title| title.text())
+        .filter(|title| !title.is_empty())
+        .unwrap_or_else(|| String::from("Untitled"))
+}
+
+fn course_title(manifest: &XmlElement, organization: Option<&XmlElement>) -> String {
+    let metadata_title = manifest
+        .child("metadata")
+        .and_then(|metadata| metadata.descendant("general"))
+        .and_then(|general| general.child("title"))
+        .map(|title| match title.descendant("string") {
+            Some(string) => string.text(),
+            None => title.text(),
+        });
+    let organization_title = organization
+        .and_then(|organization| organization.child("title"))
+        .map(|title| title.text());
+    metadata_title
+        .into_iter()
+        .chain(organization_title)
+        .chain(manifest.attribute("identifier").map(String::from))
+        .find(|title| !title.is_empty())
+        .unwrap_or_else(|| String::from("Imported course"))
+}
+
+/// Web contents are addressed by their path in the cartridge. Web links
+/// usually keep their url in a separate file that is not part of the
+/// manifest, so only links with the url inline can be converted.
+fn resource_url(resource: &XmlElement) -> Option<String> {
+    let resource_type = resource.attribute("type").unwrap_or_default();
+    let url = if resource_type.starts_with(WEB_LINK_PREFIX) {
+        resource.descendant("url").and_then(|url| url.attribute("href"))
+    } else {
+        resource
+            .attribute("href")
+            .or_else(|| resource.child("file").and_then(|file| file.attribute("href")))
+    };
+    url.map(String::from)
+}
+
+struct Converter<'a> {
+    resources: Vec<&'a XmlElement>,
+    unsupported: Vec<UnsupportedResource>,
+}
+
+impl<'a> Converter<'a> {
+    /// Adds the item's resource, then those of the items nested under it.
+    fn collect_contents(&mut self, item: &XmlElement, contents: &mut Vec<ContentRecord>) {
+        if let Some(resource_identifier) = item.attribute("identifierref") {
+            let item_identifier = item.attribute("identifier").unwrap_or_default().to_string();
+            let name = item_title(item);
+            let resource = self
+                .resources
+                .iter()
+                .find(|resource| resource.attribute("identifier") == Some(resource_identifier))
+                .cloned();
+            let resource_type = resource
+                .and_then(|resource| resource.attribute("type"))
+                .unwrap_or("missing")
+                .to_string();
+            let supported = resource_type == WEB_CONTENT || resource_type.starts_with(WEB_LINK_PREFIX);
+
+            match resource.and_then(|resource| resource_url(resource)) {
+                Some(url) if supported => contents.push(ContentRecord {
+                    source_address: Address::from(item_identifier),
+                    hash: bundle::content_hash(&name, &url, ""),
+                    name,
+                    url,
+                    description: String::new(),
+                }),
+                _ => self.unsupported.push(UnsupportedResource {
+                    item_identifier,
+                    item_title: name,
+                    resource_identifier: resource_identifier.to_string(),
+                    resource_type,
+                }),
+            }
+        }
+        for child in item.children_named("item") {
+            self.collect_contents(child, contents);
+        }
+    }
+}
+
+/// Converts the outline of an IMS Common Cartridge `imsmanifest.xml`.
+/// Each top-level item of the first organization becomes a module holding
+/// the web contents and web links found under it, nested items flattened in
+/// order. A top-level item that is itself a supported link becomes a module
+/// of its own.
+/// Identifiers from the manifest stand in for source addresses, so the
+/// `import_course` report maps them to the new entries.
+pub fn convert_cartridge(manifest: String) -> ZomeApiResult<CartridgeConversion> {
+    let root = Parser { text: &manifest, position: 0 }.parse_document()?;
+    if root.name != "manifest" {
+        return Err(ZomeApiError::from(String::from("This is not an IMS manifest")));
+    }
+
+    let organization = root
+        .child("organizations")
+        .and_then(|organizations| organizations.child("organization"));
+    let mut converter = Converter {
+        resources: root
+            .child("resources")
+            .map(|resources| resources.children_named("resource").collect())
+            .unwrap_or_default(),
+        unsupported: Vec::new(),
+    };
+
+    let mut top_items: Vec<&XmlElement> = organization
+        .map(|organization| organization.children_named("item").collect())
+        .unwrap_or_default();
+    // Common Cartridge wraps the whole outline in a single root item
+    if top_items.len() == 1 && top_items[0].attribute("identifierref").is_none() {
+        let root_item = top_items[0];
+        top_items = root_item.children_named("item").collect();
+    }
+
+    let mut modules = Vec::new();
+    for (position, item) in top_items.into_iter().enumerate() {
+        let mut contents = Vec::new();
+        converter.collect_contents(item, &mut contents);
+        // An unsupported link on its own is reported, not kept as an empty module
+        if item.attribute("identifierref").is_some() && contents.is_empty() {
+            continue;
+        }
+        let identifier = item
+            .attribute("identifier")
+            .map(String::from)
+            .unwrap_or_else(|| format!("item-{}", position + 1));
+        modules.push(ModuleRecord {
+            source_address: Address::from(identifier),
+            title: item_title(item),
+            release_offset: None,
+            release_at: None,
+            contents,
+        });
+    }
+
+    let now = clock::now()?;
+    Ok(CartridgeConversion {
+        bundle: CourseBundle {
+            format: String::from(bundle::BUNDLE_FORMAT),
+            schema_version: bundle::SCHEMA_VERSION,
+            exported_at: now.clone(),
+            source: BundleSource {
+                dna_address: DNA_ADDRESS.to_string().into(),
+                course_address: Address::from(root.attribute("identifier").unwrap_or_default()),
+                teacher_address: AGENT_ADDRESS.to_string().into(),
+            },
+            course: CourseRecord {
+                title: course_title(&root, organization),
+                created_at: now.clone(),
+                updated_at: now,
+                prerequisites: Vec::new(),
+                max_students: None,
+                enrollment_policy: EnrollmentPolicy::default(),
+                private: false,
+                encrypted: false,
+                price: None,
+            },
+            modules,
+        },
+        unsupported: converter.unsupported,
+    })
+}
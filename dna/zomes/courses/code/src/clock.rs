@@ -0,0 +1,107 @@
+/************************ Import Required Libraries */
+use hdk::prelude::*;
+
+use hdk::holochain_core_types::chain_header::ChainHeader;
+use hdk::holochain_core_types::time::Iso8601;
+use holochain_wasm_utils::api_serialization::QueryArgsNames;
+use std::convert::TryFrom;
+use std::time::Duration;
+/******************************************* */
+
+/// How far the time written in an entry may drift from its header's timestamp.
+const TIME_TOLERANCE_SECS: u64 = 300;
+
+/// Zome code has no clock of its own, so write paths read the current time
+/// from the header of a tick committed to the agent's private chain.
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct ClockTick {
+    sequence: usize,
+}
+
+impl ClockTick {
+    pub fn entry(&self) -> Entry {
+        Entry::App("clock_tick".into(), self.into())
+    }
+}
+
+////////////////////Clock Tick Entry Definition
+pub fn entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "clock_tick",
+        description: "a private marker whose header tells the agent the current time",
+        sharing: Sharing::Private,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: | _validation_data: hdk::EntryValidationData<ClockTick> | {
+            Ok(())
+        }
+    )
+}
+
+/*********************** Time Validations */
+
+/// Checks an entry's `created_at` on create and keeps it fixed afterwards,
+/// and that `updated_at` is close to the time its header was written.
+pub fn validate_times(
+    header: &ChainHeader,
+    created_at: &Iso8601,
+    updated_at: &Iso8601,
+    previous_created_at: Option<&Iso8601>,
+) -> Result<(), String> {
+    match previous_created_at {
+        None if created_at != updated_at => {
+            return Err(String::from("A new entry must be created and updated at the same time"))
+        }
+        Some(previous_created_at) if created_at != previous_created_at => {
+            return Err(String::from("Cannot change the creation time of an entry"))
+        }
+        _ => (),
+    }
+
+    let tolerance = Duration::from_secs(TIME_TOLERANCE_SECS);
+    let earliest = (header.timestamp().clone() - tolerance).map_err(|error| error.to_string())?;
+    let latest = (header.timestamp().clone() + tolerance).map_err(|error| error.to_string())?;
+    if updated_at < &earliest || updated_at > &latest {
+        return Err(String::from("Entry time does not match the time it was committed"));
+    }
+    Ok(())
+}
+
+/// Helper Functions
+/// When each entry matching `entry_type_names` was committed to the agent's chain.
+pub fn commit_times(entry_type_names: &str) -> ZomeApiResult<Vec<Iso8601>> {
+    let result = hdk::query_result(
+        QueryArgsNames::QueryName(entry_type_names.into()),
+        QueryArgsOptions {
+            start: 0,
+            limit: 0,
+            headers: true,
+            entries: false,
+        },
+    )?;
+    match result {
+        QueryResult::Headers(headers) => Ok(headers.into_iter().map(|header| header.timestamp().clone()).collect()),
+        _ => Err(ZomeApiError::from(String::from("Unexpected query result"))),
+    }
+}
+
+/// The time of the agent's latest commit, read without writing anything.
+/// It lags behind the wall clock while the agent is idle, so checks against
+/// it may open scheduled items late but never early; see
+/// `schedule::ReleaseClock` for reads that need the fresh time.
+pub fn now() -> ZomeApiResult<Iso8601> {
+    commit_times("**")?
+        .into_iter()
+        .max()
+        .ok_or_else(|| ZomeApiError::from(String::from("Could not read the current time")))
+}
+
+/// The current time for an entry about to be committed. Its embedded time has
+/// to match its header, so a tick is committed first. Reads only call this
+/// when a stale time would keep something locked.
+pub fn commit_time() -> ZomeApiResult<Iso8601> {
+    let sequence = commit_times("clock_tick")?.len();
+    hdk::commit_entry(&ClockTick { sequence }.entry())?;
+    now()
+}
@@ -0,0 +1,325 @@
+/************************ Import Required Libraries */
+use hdk::prelude::*;
+
+use crate::clock;
+use crate::course;
+use crate::course::Course;
+use crate::enrollment;
+use crate::enrollment::EnrollmentPolicy;
+use crate::waitlist;
+use chrono::{DateTime, FixedOffset};
+use hdk::holochain_core_types::time::Iso8601;
+use hdk::AGENT_ADDRESS;
+use std::convert::TryFrom;
+/******************************************* */
+
+/// One run of a course (e.g. a term), with its own roster and seats. Students
+/// can join it until its end date.
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct Cohort {
+    pub course_address: Address,
+    pub title: String,
+    pub start_date: u64,
+    pub end_date: u64,
+    pub max_students: Option<u32>, // None means the cohort only shares the course's seats
+}
+
+impl Cohort {
+    pub fn new(course_address: Address, title: String, start_date: u64, end_date: u64, max_students: Option<u32>) -> Self {
+        Cohort {
+            course_address,
+            title,
+            start_date,
+            end_date,
+            max_students,
+        }
+    }
+
+    pub fn entry(&self) -> Entry {
+        Entry::App("cohort".into(), self.into())
+    }
+}
+
+////////////////////Cohort Entry Definition
+pub fn entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "cohort",
+        description: "a run of a course between two dates, with its own roster",
+        sharing: Sharing::Public,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: | validation_data: hdk::EntryValidationData<Cohort> | {
+            match validation_data {
+                EntryValidationData::Create {entry, validation_data} => {
+                    validate_teacher(&validation_data.sources(), &entry.course_address)?;
+                    validate_cohort(&entry)
+                },
+                EntryValidationData::Modify {new_entry, old_entry, validation_data, ..} => {
+                    if new_entry.course_address != old_entry.course_address {
+                        return Err(String::from("Cannot move a cohort to another course"));
+                    }
+                    validate_teacher(&validation_data.sources(), &old_entry.course_address)?;
+                    validate_cohort(&new_entry)
+                },
+                EntryValidationData::Delete {old_entry, validation_data, ..} => {
+                    validate_teacher(&validation_data.sources(), &old_entry.course_address)?;
+                    Ok(())
+                }
+            }
+        },
+        links: [
+            from!( // all the cohorts a course has run
+                "course",
+                link_type: "course->cohorts",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | _validation_data: hdk::LinkValidationData | {
+                    Ok(())
+                }
+            ),
+            to!( // the roster of a cohort
+                "%agent_id",
+                link_type: "cohort->students",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, validation_data } => {
+                            validate_roster_add(
+                                link.link.base(),
+                                link.link.target(),
+                                &validation_data.sources(),
+                                validation_data.package.chain_header.timestamp(),
+                            )?;
+                            validate_capacity(link.link.base(), link.link.target())?;
+                            Ok(())
+                        },
+                        hdk::LinkValidationData::LinkRemove { link, validation_data } => {
+                            validate_roster_change(link.link.base(), link.link.target(), &validation_data.sources())?;
+                            Ok(())
+                        }
+                    }
+                }
+            )
+        ]
+    )
+}
+
+/*********************** Cohort Validations */
+fn validate_teacher(signing_addresses: &Vec<Address>, course_address: &Address) -> ZomeApiResult<()> {
+    let course: Course = hdk::utils::get_as_type(course_address.clone())?;
+    if !signing_addresses.contains(&course.teacher_address) {
+        return Err(ZomeApiError::from(String::from(
+            "Only the teacher can manage the cohorts of a course",
+        )));
+    }
+    Ok(())
+}
+
+fn validate_cohort(cohort: &Cohort) -> Result<(), String> {
+    if cohort.title.is_empty() {
+        return Err(String::from("A cohort needs a title"));
+    }
+    if cohort.start_date >= cohort.end_date {
+        return Err(String::from("A cohort must end after it starts"));
+    }
+    waitlist::validate_max_students(&cohort.max_students)
+}
+
+/// The teacher can place anyone. Students only place themselves as they join
+/// the course: once enrolled, while in no other cohort of it, before the
+/// cohort ends and, unless the course is open, in the cohort the teacher
+/// approved.
+fn validate_roster_add(
+    cohort_address: &Address,
+    student_address: &Address,
+    signing_addresses: &Vec<Address>,
+    added_at: &Iso8601,
+) -> ZomeApiResult<()> {
+    let cohort: Cohort = hdk::utils::get_as_type(cohort_address.clone())?;
+    let course: Course = hdk::utils::get_as_type(cohort.course_address.clone())?;
+    if signing_addresses.contains(&course.teacher_address) {
+        return Ok(());
+    }
+    if !signing_addresses.contains(student_address) {
+        return Err(ZomeApiError::from(String::from(
+            "Only the teacher can place other students in a cohort",
+        )));
+    }
+    if !course::get_students(cohort.course_address.clone())?.contains(student_address) {
+        return Err(ZomeApiError::from(String::from("Student is not enrolled in this course")));
+    }
+    for other_address in get_cohorts(cohort.course_address.clone())? {
+        if &other_address != cohort_address && get_students(other_address)?.contains(student_address) {
+            return Err(ZomeApiError::from(String::from(
+                "Only the teacher can move students between cohorts",
+            )));
+        }
+    }
+    if has_ended(&cohort, added_at) {
+        return Err(ZomeApiError::from(String::from("This cohort has ended")));
+    }
+    if course.enrollment_policy != EnrollmentPolicy::Open
+        && !enrollment::is_approved_for(&cohort.course_address, student_address, &Some(cohort_address.clone()))?
+    {
+        return Err(ZomeApiError::from(String::from(
+            "The teacher has not approved this cohort",
+        )));
+    }
+    Ok(())
+}
+
+/// Students can take themselves off a roster; the teacher can take anyone off.
+fn validate_roster_change(cohort_address: &Address, student_address: &Address, signing_addresses: &Vec<Address>) -> ZomeApiResult<()> {
+    if signing_addresses.contains(student_address) {
+        return Ok(());
+    }
+    let cohort: Cohort = hdk::utils::get_as_type(cohort_address.clone())?;
+    validate_teacher(signing_addresses, &cohort.course_address)
+}
+
+fn validate_capacity(cohort_address: &Address, student_address: &Address) -> ZomeApiResult<()> {
+    let cohort: Cohort = hdk::utils::get_as_type(cohort_address.clone())?;
+    if let Some(max_students) = cohort.max_students {
+        let students = get_students(cohort_address.clone())?;
+        let taken = students.iter().filter(|student| *student != student_address).count();
+        if taken >= max_students as usize {
+            return Err(ZomeApiError::from(String::from("Cohort is full")));
+        }
+    }
+    Ok(())
+}
+
+/// Helper Functions
+fn has_ended(cohort: &Cohort, now: &Iso8601) -> bool {
+    DateTime::<FixedOffset>::from(now).timestamp() >= cohort.end_date as i64
+}
+
+fn is_full(cohort: &Cohort, cohort_address: &Address) -> ZomeApiResult<bool> {
+    match cohort.max_students {
+        Some(max_students) => Ok(get_students(cohort_address.clone())?.len() >= max_students as usize),
+        None => Ok(false),
+    }
+}
+
+pub fn create(
+    course_address: Address,
+    title: String,
+    start_date: u64,
+    end_date: u64,
+    max_students: Option<u32>,
+) -> ZomeApiResult<Address> {
+    let cohort = Cohort::new(course_address.clone(), title, start_date, end_date, max_students);
+    let cohort_address = hdk::commit_entry(&cohort.entry())?;
+    hdk::link_entries(&course_address, &cohort_address, "course->cohorts", "")?;
+    Ok(cohort_address)
+}
+
+pub fn update(
+    cohort_address: Address,
+    title: String,
+    start_date: u64,
+    end_date: u64,
+    max_students: Option<u32>,
+) -> ZomeApiResult<Address> {
+    let mut cohort: Cohort = hdk::utils::get_as_type(cohort_address.clone())?;
+    cohort.title = title;
+    cohort.start_date = start_date;
+    cohort.end_date = end_date;
+    cohort.max_students = max_students;
+    hdk::update_entry(cohort.entry(), &cohort_address)
+}
+
+pub fn get_cohorts(course_address: Address) -> ZomeApiResult<Vec<Address>> {
+    let links = hdk::get_links(
+        &course_address,
+        LinkMatch::Exactly("course->cohorts"),
+        LinkMatch::Any,
+    )?;
+    Ok(links.addresses())
+}
+
+pub fn get_students(cohort_address: Address) -> ZomeApiResult<Vec<Address>> {
+    let links = hdk::get_links(
+        &cohort_address,
+        LinkMatch::Exactly("cohort->students"),
+        LinkMatch::Any,
+    )?;
+    Ok(links.addresses())
+}
+
+/// The roster of one cohort of the course.
+pub fn get_course_students(course_address: Address, cohort_address: Address) -> ZomeApiResult<Vec<Address>> {
+    let cohort: Cohort = hdk::utils::get_as_type(cohort_address.clone())?;
+    if cohort.course_address != course_address {
+        return Err(ZomeApiError::from(String::from("This cohort is not part of the course")));
+    }
+    get_students(cohort_address)
+}
+
+/// Checks the cohort a student asked for before they are enrolled. Courses
+/// without cohorts take no cohort; courses with cohorts require one.
+pub fn check_selection(course_address: &Address, cohort_address: &Option<Address>) -> ZomeApiResult<()> {
+    let cohorts = get_cohorts(course_address.clone())?;
+    match cohort_address {
+        None if cohorts.is_empty() => Ok(()),
+        None => Err(ZomeApiError::from(String::from("Choose a cohort to enrol in"))),
+        Some(cohort_address) => {
+            if !cohorts.contains(cohort_address) {
+                return Err(ZomeApiError::from(String::from("This cohort is not part of the course")));
+            }
+            let cohort: Cohort = hdk::utils::get_as_type(cohort_address.clone())?;
+            if has_ended(&cohort, &clock::now()?) {
+                return Err(ZomeApiError::from(String::from("This cohort has ended")));
+            }
+            if is_full(&cohort, cohort_address)? {
+                return Err(ZomeApiError::from(String::from("Cohort is full")));
+            }
+            Ok(())
+        }
+    }
+}
+
+pub fn join(cohort_address: &Option<Address>, student_address: &Address) -> ZomeApiResult<()> {
+    if let Some(cohort_address) = cohort_address {
+        hdk::link_entries(cohort_address, student_address, "cohort->students", "")?;
+    }
+    Ok(())
+}
+
+/// Takes the student off the roster of every cohort of the course.
+pub fn leave(course_address: &Address, student_address: &Address) -> ZomeApiResult<()> {
+    for cohort_address in get_cohorts(course_address.clone())? {
+        if get_students(cohort_address.clone())?.contains(student_address) {
+            hdk::remove_link(&cohort_address, student_address, "cohort->students", "")?;
+        }
+    }
+    Ok(())
+}
+
+/// Places an enrolled student in `cohort_address`, taking them out of
+/// whichever cohort of the same course they were in before.
+pub fn move_student(cohort_address: Address, student_address: Address) -> ZomeApiResult<Address> {
+    let cohort: Cohort = hdk::utils::get_as_type(cohort_address.clone())?;
+    let course: Course = hdk::utils::get_as_type(cohort.course_address.clone())?;
+    if course.teacher_address != Address::from(AGENT_ADDRESS.to_string()) {
+        return Err(ZomeApiError::from(String::from(
+            "Only the teacher can move students between cohorts",
+        )));
+    }
+    if !course::get_students(cohort.course_address.clone())?.contains(&student_address) {
+        return Err(ZomeApiError::from(String::from("Student is not enrolled in this course")));
+    }
+    if get_students(cohort_address.clone())?.contains(&student_address) {
+        return Err(ZomeApiError::from(String::from("Student is already in this cohort")));
+    }
+    if is_full(&cohort, &cohort_address)? {
+        return Err(ZomeApiError::from(String::from("Cohort is full")));
+    }
+
+    leave(&cohort.course_address, &student_address)?;
+    hdk::link_entries(&cohort_address, &student_address, "cohort->students", "")
+}
@@ -1,8 +1,17 @@
 /************************ Import Required Libraries */
+use crate::analytics;
+use crate::clock;
 use crate::course;
 use crate::course::Course;
+use crate::encryption;
+use crate::invite;
+use crate::message;
+use crate::message::DirectMessage;
+use crate::moderation;
 use crate::module::Module;
+use crate::schedule;
 use hdk::holochain_core_types::dna::entry_types::Sharing;
+use hdk::holochain_core_types::time::Iso8601;
 use hdk::holochain_core_types::{entry::Entry, validation::EntryValidationData};
 use hdk::holochain_json_api::{error::JsonError, json::JsonString};
 use hdk::holochain_persistence_api::cas::content::Address;
@@ -16,6 +25,7 @@ use holochain_wasm_utils::api_serialization::{
     get_entry::{GetEntryOptions, GetEntryResult},
     get_links::GetLinksOptions,
 };
+use serde_json::json;
 use std::convert::TryFrom;
 /******************************************* */
 
@@ -24,24 +34,76 @@ pub struct Content {
     name: String,
     url: String,
     description: String,
-    timestamp: u64,
-    module_address: Address
+    created_at: Iso8601,
+    updated_at: Iso8601,
+    module_address: Address,
+    key_version: Option<u32>, // Set when url and description are encrypted with the course key
 }
 
 impl Content {
-    pub fn new(name: String, module_address: Address, url: String, timestamp: u64, description: String) -> Self {
+    pub fn new(name: String, module_address: Address, url: String, description: String, created_at: Iso8601) -> Self {
         Content {
             name,
             url,
             description,
-            timestamp,
-            module_address
+            created_at: created_at.clone(),
+            updated_at: created_at,
+            module_address,
+            key_version: None,
         }
     }
 
     pub fn entry(&self) -> Entry {
         Entry::App("content".into(), self.into())
     }
+
+    pub fn touch(&mut self) -> ZomeApiResult<()> {
+        self.updated_at = clock::commit_time()?;
+        Ok(())
+    }
+
+    pub fn key_version(&self) -> Option<u32> {
+        self.key_version
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn created_at(&self) -> &Iso8601 {
+        &self.created_at
+    }
+
+    pub fn module_address(&self) -> &Address {
+        &self.module_address
+    }
+
+    // sealed fields are bound to their module and name, so they cannot be swapped around
+    fn field_context(&self, field: &str) -> String {
+        format!("{}:{}", self.module_address, field)
+    }
+
+    pub fn encrypt(mut self, key_version: u32, key: &[u8; 32]) -> ZomeApiResult<Self> {
+        self.url = encryption::encrypt_field(key, &self.field_context("url"), &self.url)?;
+        self.description = encryption::encrypt_field(key, &self.field_context("description"), &self.description)?;
+        self.key_version = Some(key_version);
+        Ok(self)
+    }
+
+    pub fn decrypt(mut self, key: &[u8; 32]) -> ZomeApiResult<Self> {
+        self.url = encryption::decrypt_field(key, &self.field_context("url"), &self.url)?;
+        self.description = encryption::decrypt_field(key, &self.field_context("description"), &self.description)?;
+        self.key_version = None;
+        Ok(self)
+    }
 }
 
 
@@ -58,6 +120,8 @@ pub fn entry_def() -> ValidatingEntryType {
             match validation_data {
                 EntryValidationData::Create {entry, validation_data} => {
                     validate_author(&validation_data.sources(), &entry.module_address)?;
+                    validate_key_version(&entry)?;
+                    clock::validate_times(&validation_data.package.chain_header, &entry.created_at, &entry.updated_at, None)?;
                     Ok(())
                 },
                 EntryValidationData::Modify {new_entry, old_entry, validation_data, ..} => {
@@ -65,6 +129,13 @@ pub fn entry_def() -> ValidatingEntryType {
                         return Err(String::from("Cannot modify the module of a content"));
                     }
                     validate_author(&validation_data.sources(), &new_entry.module_address)?;
+                    validate_key_version(&new_entry)?;
+                    clock::validate_times(
+                        &validation_data.package.chain_header,
+                        &new_entry.created_at,
+                        &new_entry.updated_at,
+                        Some(&old_entry.created_at),
+                    )?;
                     Ok(())
                 },
                 EntryValidationData::Delete {old_entry, validation_data,  ..} => {
@@ -83,18 +154,118 @@ fn validate_author(signing_addresses: &Vec<Address>, module_address: &Address) -
     if !signing_addresses.contains(&course.teacher_address) {
         return Err(ZomeApiError::from(String::from("Error: Only the teacher can create or modify a content for module")));
     }
+    course::validate_member(&course.teacher_address)?;
+    Ok(())
+}
+
+fn validate_key_version(content: &Content) -> ZomeApiResult<()> {
+    let module: Module = hdk::utils::get_as_type(content.module_address.clone())?;
+    let course: Course = hdk::utils::get_as_type(module.course_address.clone())?;
+    if content.key_version != course.content_key_version {
+        return Err(ZomeApiError::from(String::from("Content must be encrypted with the current course key")));
+    }
     Ok(())
 }
 
 /// Helper Functions
-pub fn create(name: String, module_address: Address, url: String, timestamp: u64, description: String) -> ZomeApiResult<Address> {
-    let new_content = Content::new(name, module_address.clone(), url, timestamp, description);
+pub fn create(name: String, module_address: Address, url: String, description: String) -> ZomeApiResult<Address> {
+    let module: Module = hdk::utils::get_as_type(module_address.clone())?;
+    let course: Course = hdk::utils::get_as_type(module.course_address.clone())?;
+    let new_content = Content::new(name, module_address.clone(), url, description, clock::commit_time()?);
+    let new_content = encryption::encrypt_content(&module.course_address, &course, new_content)?;
     let new_content_entry = new_content.entry();
     let new_content_address = hdk::commit_entry(&new_content_entry)?;
     hdk::link_entries(&module_address, &new_content_address, "module->contents", "")?;
     Ok(new_content_address)
 }
 
+/// Opens a content for the caller and, once it could be read, records the
+/// view for the course analytics.
+pub fn get(content_address: Address) -> ZomeApiResult<Content> {
+    let content = read(content_address.clone())?;
+    let module: Module = hdk::utils::get_as_type(content.module_address.clone())?;
+    let course: Course = hdk::utils::get_as_type(module.course_address.clone())?;
+    analytics::record_view(&content_address, &module.course_address, &course)?;
+    Ok(content)
+}
+
+/// Public courses are read straight from the DHT. Contents of private courses
+/// are served by the teacher's node to enrolled students holding an access
+/// grant.
+/// Encrypted contents come back decrypted for whoever holds the course key.
+pub fn read(content_address: Address) -> ZomeApiResult<Content> {
+    let content: Content = hdk::utils::get_as_type(content_address.clone())?;
+    let module: Module = hdk::utils::get_as_type(content.module_address.clone())?;
+    let course: Course = hdk::utils::get_as_type(module.course_address.clone())?;
+    moderation::validate_visible(&course, &AGENT_ADDRESS, &[&module.course_address, &content.module_address, &content_address])?;
+    schedule::validate_released(&module, &AGENT_ADDRESS)?;
+
+    let content = if !course.private || course.teacher_address == Address::from(AGENT_ADDRESS.to_string()) {
+        content
+    } else {
+        let token = invite::get_my_access_token(&module.course_address)?;
+        message::send(
+            course.teacher_address.clone(),
+            DirectMessage::ContentRequest { content_address, token },
+        )?
+    };
+
+    encryption::decrypt_content(&module.course_address, &course, content)
+}
+
+/// Runs on the teacher's node. The student's token is presented to the
+/// conductor through a zome call, so `serve_content` only runs under a grant
+/// this node issued. Contents of private courses are always encrypted, and
+/// the student decrypts them with the course key.
+pub fn handle_content_request(student_address: &Address, content_address: Address, token: Address) -> ZomeApiResult<Content> {
+    let content_json: JsonString = hdk::call(
+        hdk::THIS_INSTANCE,
+        "courses",
+        token.clone(),
+        "serve_content",
+        json!({"content_address": content_address, "student_address": student_address, "token": token}).into(),
+    )?;
+
+    let content: Result<ZomeApiResult<Content>, _> = serde_json::from_str(&content_json.to_string());
+
+    match content {
+        Ok(content) => content,
+        Err(_) => Err(ZomeApiError::from(String::from(
+            "No valid access grant for this course",
+        ))),
+    }
+}
+
+/// Only callable with an access grant, from `handle_content_request`.
+pub fn serve(content_address: Address, student_address: &Address, token: &Address) -> ZomeApiResult<Content> {
+    let content: Content = hdk::utils::get_as_type(content_address.clone())?;
+    let module: Module = hdk::utils::get_as_type(content.module_address.clone())?;
+    let course: Course = hdk::utils::get_as_type(module.course_address.clone())?;
+    invite::validate_access_token(student_address, &module.course_address, token)?;
+    if !course::get_students(module.course_address.clone())?.contains(student_address) {
+        return Err(ZomeApiError::from(String::from(
+            "Only enrolled students can read this course",
+        )));
+    }
+    moderation::validate_visible(&course, student_address, &[&module.course_address, &content.module_address, &content_address])?;
+    schedule::validate_released(&module, student_address)?;
+    Ok(content)
+}
+
+/// The contents of a module that is not released yet, or hidden by a
+/// moderator, stay hidden from students.
+pub fn get_released_contents(module_address: &Address) -> ZomeApiResult<Vec<Address>> {
+    let module: Module = hdk::utils::get_as_type(module_address.clone())?;
+    let course: Course = hdk::utils::get_as_type(module.course_address.clone())?;
+    if course.teacher_address == Address::from(AGENT_ADDRESS.to_string()) {
+        return get_contents(module_address);
+    }
+    if !schedule::ReleaseClock::new()?.is_released(&module, &AGENT_ADDRESS)? || moderation::is_hidden(module_address)? {
+        return Ok(Vec::new());
+    }
+    moderation::filter_hidden(get_contents(module_address)?)
+}
+
 pub fn get_contents(module_address: &Address) -> ZomeApiResult<Vec<Address>> {
     let links = hdk::get_links(
         &module_address, 
@@ -115,8 +286,13 @@ pub fn delete(content_address: Address) -> ZomeApiResult<Address> {
 
 pub fn update(content_address: Address, name: String, url: String, description: String) -> ZomeApiResult<Address> {
     let mut content: Content = hdk::utils::get_as_type(content_address.clone())?;
+    let module: Module = hdk::utils::get_as_type(content.module_address.clone())?;
+    let course: Course = hdk::utils::get_as_type(module.course_address.clone())?;
     content.description = description;
     content.name = name;
     content.url = url;
+    content.key_version = None;
+    content.touch()?;
+    let content = encryption::encrypt_content(&module.course_address, &course, content)?;
     hdk::update_entry(content.entry(), &content_address)
 }
\ No newline at end of file
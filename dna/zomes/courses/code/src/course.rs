@@ -6,9 +6,11 @@ use hdk::{
 };
 
 use hdk::holochain_core_types::dna::entry_types::Sharing;
+use hdk::holochain_core_types::time::Iso8601;
 use hdk::holochain_core_types::{entry::Entry, validation::EntryValidationData};
+use hdk::holochain_core_types::chain_header::ChainHeader;
 use holochain_wasm_utils::api_serialization::{
-    get_entry::{GetEntryOptions, GetEntryResult},
+    get_entry::{GetEntryOptions, GetEntryResult, GetEntryResultType, StatusRequestKind},
     get_links::GetLinksOptions,
 };
 
@@ -19,37 +21,64 @@ use hdk::prelude::LinkMatch;
 use hdk::ValidationData;
 use std::convert::TryFrom;
 use serde_json::json;
+use crate::clock;
+use crate::cohort;
+use crate::encryption;
+use crate::enrollment;
+use crate::enrollment::EnrollmentPolicy;
+use crate::fork;
+use crate::message;
+use crate::message::DirectMessage;
+use crate::moderation;
+use crate::prerequisite;
+use crate::purchase;
+use crate::transfer;
+use crate::waitlist;
 /******************************************* */
 
 
 #[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
 pub struct Course {
-    title: String,
+    pub title: String,
     pub teacher_address: Address,
     pub modules: Vec<Address>, // Implicit link, as relationship with module
-    pub timestamp: u64,
+    pub created_at: Iso8601,
+    pub updated_at: Iso8601,
+    pub prerequisites: Vec<Address>, // Courses a student must complete before enrolling
+    pub max_students: Option<u32>, // None means unlimited seats
+    pub enrollment_policy: EnrollmentPolicy,
+    pub private: bool, // Contents are only served to holders of an access grant
+    pub content_key_version: Option<u32>, // Set when contents are encrypted with a course key
+    pub price: Option<u64>, // Ledger credits a student pays the teacher, None for free courses
+    pub forkable: bool, // Whether other teachers may clone the course
 }
 
 impl Course {
-    pub fn new(title: String, owner: Address, timestamp: u64) -> Self {
+    pub fn new(title: String, owner: Address, created_at: Iso8601) -> Self {
         Course {
             title,
             teacher_address: owner,
             modules: Vec::default(),
-            timestamp
-        }
-    }
-    pub fn from(title: String, owner: Address, timestamp: u64, modules: Vec<Address>) -> Self {
-        Course {
-            title,
-            teacher_address: owner,
-            modules,
-            timestamp
+            created_at: created_at.clone(),
+            updated_at: created_at,
+            prerequisites: Vec::default(),
+            max_students: None,
+            enrollment_policy: EnrollmentPolicy::default(),
+            private: false,
+            content_key_version: None,
+            price: None,
+            forkable: true,
         }
     }
     pub fn entry(&self) -> Entry {
         Entry::App("course".into(), self.into())
     }
+
+    /// Stamps the course with the current time before it is updated.
+    pub fn touch(&mut self) -> ZomeApiResult<()> {
+        self.updated_at = clock::commit_time()?;
+        Ok(())
+    }
 }
 
 ////////////////////Course Entry Definition
@@ -69,18 +98,41 @@ pub fn course_entry_def() -> ValidatingEntryType {
                     if !validation_data.sources().contains(&entry.teacher_address) {
                         return Err(String::from("Only the teacher can create their courses"));
                     }
-                    validate_teacher_is_member(&entry.teacher_address)?;
+                    validate_teacher_role(&entry.teacher_address)?;
+                    prerequisite::validate_prerequisites(
+                        validation_data.package.chain_header.entry_address(),
+                        &entry.prerequisites,
+                    )?;
+                    waitlist::validate_max_students(&entry.max_students)?;
+                    validate_private(&entry)?;
+                    clock::validate_times(&validation_data.package.chain_header, &entry.created_at, &entry.updated_at, None)?;
                     validate_course_title(&entry.title)
                 },
 
-                EntryValidationData::Modify {new_entry, old_entry, validation_data, ..} => {
+                EntryValidationData::Modify {new_entry, old_entry, old_entry_header, validation_data} => {
                     if new_entry.teacher_address != old_entry.teacher_address {
-                        return Err(String::from("Cannot change the teacher of the course"));
-                    }
-
-                    if !validation_data.sources().contains(&old_entry.teacher_address) {
+                        // the teacher can only change by accepting a course transfer
+                        transfer::validate_transfer(
+                            &original_address(&old_entry_header)?,
+                            old_entry_header.entry_address(),
+                            &old_entry,
+                            &new_entry,
+                            &validation_data.sources(),
+                        )?;
+                    } else if !validation_data.sources().contains(&old_entry.teacher_address) {
                         return Err(String::from("Only the teacher can modify their courses"));
                     }
+                    validate_member(&new_entry.teacher_address)?;
+
+                    prerequisite::validate_prerequisites(&original_address(&old_entry_header)?, &new_entry.prerequisites)?;
+                    waitlist::validate_max_students(&new_entry.max_students)?;
+                    validate_private(&new_entry)?;
+                    clock::validate_times(
+                        &validation_data.package.chain_header,
+                        &new_entry.created_at,
+                        &new_entry.updated_at,
+                        Some(&old_entry.created_at),
+                    )?;
 
                     validate_course_title(&new_entry.title)
                 },
@@ -133,10 +185,60 @@ pub fn course_entry_def() -> ValidatingEntryType {
                 validation_package: || {
                     hdk::ValidationPackageDefinition::Entry
                 },
-                validation: | _validation_data: hdk::LinkValidationData | {
-                    Ok(())
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, validation_data } => {
+                            enrollment::validate_enrollment(
+                                link.link.base(),
+                                link.link.target(),
+                                &validation_data.sources(),
+                            )?;
+                            validate_member(link.link.target())?;
+                            waitlist::validate_capacity(link.link.base(), link.link.target())?;
+                            purchase::validate_payment(link.link.base(), link.link.target(), link.link.tag())?;
+                            Ok(())
+                        },
+                        hdk::LinkValidationData::LinkRemove { .. } => Ok(())
+                    }
+                }
+            ),
+            to!( // the course this one was cloned from
+                "course",
+                link_type: "course->forked_from",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, validation_data } => {
+                            fork::validate_fork(link.link.base(), link.link.target(), &validation_data.sources())?;
+                            Ok(())
+                        },
+                        hdk::LinkValidationData::LinkRemove { .. } => {
+                            Err(String::from("The origin of a cloned course cannot be removed"))
+                        }
+                    }
+                }
+            ),
+            to!( // students waiting for a seat once the course is full
+                "%agent_id",
+                link_type: "course->waitlist",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, validation_data } => {
+                            waitlist::validate_waitlist_change(link.link.base(), link.link.target(), &validation_data.sources())?;
+                            Ok(())
+                        },
+                        hdk::LinkValidationData::LinkRemove { link, validation_data } => {
+                            waitlist::validate_waitlist_change(link.link.base(), link.link.target(), &validation_data.sources())?;
+                            Ok(())
+                        }
+                    }
                 }
-            )   
+            )
         ]
     )
 }
@@ -178,7 +280,7 @@ fn anchor_address() -> ZomeApiResult<Address> {
 }
 
 /*********************** Course Validations */
-fn validate_course_title(title: &str) -> Result<(), String> {
+pub fn validate_course_title(title: &str) -> Result<(), String> {
     if title.len() > 50 {
         Err("Course title is too long".into())
     } else {
@@ -186,14 +288,49 @@ fn validate_course_title(title: &str) -> Result<(), String> {
     }
 }
 
-fn validate_teacher_is_member(teacher_address: &Address) -> ZomeApiResult<()> {
-    hdk::debug(format!("qwertyuio {}", hdk::PUBLIC_TOKEN.to_string()))?;
+/// Contents are public entries, so a private course may only publish them
+/// encrypted, and only the teacher may let students in to hand them the key.
+pub fn validate_private(course: &Course) -> Result<(), String> {
+    if !course.private {
+        return Ok(());
+    }
+    if course.content_key_version.is_none() {
+        return Err("Private courses must encrypt their contents".into());
+    }
+    if course.enrollment_policy != EnrollmentPolicy::InviteOnly {
+        return Err("Private courses are invite only".into());
+    }
+    Ok(())
+}
+
+/// Asks the members zome whether `agent_address` holds `role`.
+pub fn has_role(agent_address: &Address, role: &str) -> ZomeApiResult<bool> {
+    let has_role_json: JsonString = hdk::call(
+        hdk::THIS_INSTANCE,
+        "members",
+        Address::from(hdk::PUBLIC_TOKEN.to_string()),
+        "has_role",
+        json!({"agent_address": agent_address, "role": role}).into(),
+    )?;
+
+    let has_role: Result<ZomeApiResult<bool>, _> = serde_json::from_str(&has_role_json.to_string());
+
+    match has_role {
+        Ok(Ok(has_role)) => Ok(has_role),
+        _ => Err(ZomeApiError::from(String::from(
+            "Could not check the roles of this agent",
+        ))),
+    }
+}
+
+/// Rejects actions by agents whose membership was revoked.
+pub fn validate_member(agent_address: &Address) -> ZomeApiResult<()> {
     let is_valid_json: JsonString = hdk::call(
-        hdk::THIS_INSTANCE, 
-        "members", 
-        Address::from(hdk::PUBLIC_TOKEN.to_string()), 
-        "is_member_valid", 
-        json!({"agent_address": teacher_address}).into(),    
+        hdk::THIS_INSTANCE,
+        "members",
+        Address::from(hdk::PUBLIC_TOKEN.to_string()),
+        "is_member_valid",
+        json!({"agent_address": agent_address}).into(),
     )?;
 
     let is_valid: Result<ZomeApiResult<bool>, _> = serde_json::from_str(&is_valid_json.to_string());
@@ -201,16 +338,95 @@ fn validate_teacher_is_member(teacher_address: &Address) -> ZomeApiResult<()> {
     match is_valid {
         Ok(Ok(true)) => Ok(()),
         _ => Err(ZomeApiError::from(String::from(
-            "Teacher address is not valid",
+            "Agent is not a valid member",
         ))),
     }
 }
 
-pub fn create(title: String, timestamp: u64) -> ZomeApiResult<Address> {
+pub fn validate_teacher_role(teacher_address: &Address) -> ZomeApiResult<()> {
+    if !has_role(teacher_address, "Teacher")? {
+        return Err(ZomeApiError::from(String::from(
+            "Teacher address does not have the teacher role",
+        )));
+    }
+    Ok(())
+}
+
+/// Everyone who has taught the course, read from all its versions.
+pub fn get_teachers(course_address: &Address) -> ZomeApiResult<Vec<Address>> {
+    let mut teachers = Vec::new();
+    if let Some(history) = hdk::get_entry_history(course_address)? {
+        for item in history.items {
+            if let Some(Entry::App(_, value)) = item.entry {
+                let course = Course::try_from(value)?;
+                if !teachers.contains(&course.teacher_address) {
+                    teachers.push(course.teacher_address);
+                }
+            }
+        }
+    }
+    Ok(teachers)
+}
+
+/// Updates are validated against the header of the version they replace,
+/// but other entries refer to a course by the address it was first committed
+/// at, so follow the updates back to it.
+pub fn original_address(header: &ChainHeader) -> ZomeApiResult<Address> {
+    let mut address = header.entry_address().clone();
+    let mut replaced = header.link_update_delete();
+    while let Some(previous_address) = replaced {
+        replaced = get_header(&previous_address)?.and_then(|previous| previous.link_update_delete());
+        address = previous_address;
+    }
+    Ok(address)
+}
+
+fn get_header(address: &Address) -> ZomeApiResult<Option<ChainHeader>> {
+    let result = hdk::get_entry_result(
+        address,
+        GetEntryOptions::new(StatusRequestKind::Initial, false, true, Default::default()),
+    )?;
+
+    match result.result {
+        GetEntryResultType::Single(item) => Ok(item
+            .headers
+            .into_iter()
+            .find(|header| header.entry_address() == address)),
+        _ => Ok(None),
+    }
+}
+
+/// Courses stay readable after their teacher's membership is revoked, but
+/// are flagged with the reason given for the revocation.
+pub fn get_flag(course_address: Address) -> ZomeApiResult<Option<String>> {
+    let course: Course = hdk::utils::get_as_type(course_address)?;
+    let revocation_json: JsonString = hdk::call(
+        hdk::THIS_INSTANCE,
+        "members",
+        Address::from(hdk::PUBLIC_TOKEN.to_string()),
+        "get_revocation",
+        json!({"agent_address": course.teacher_address}).into(),
+    )?;
+
+    let revocation: Result<ZomeApiResult<Option<serde_json::Value>>, _> = serde_json::from_str(&revocation_json.to_string());
+
+    match revocation {
+        Ok(Ok(Some(revocation))) => Ok(Some(format!(
+            "Teacher membership revoked: {}",
+            revocation["reason"].as_str().unwrap_or_default()
+        ))),
+        Ok(Ok(None)) => Ok(None),
+        _ => Err(ZomeApiError::from(String::from(
+            "Could not check the teacher of this course",
+        ))),
+    }
+}
+
+pub fn create(title: String) -> ZomeApiResult<Address> {
     let anchor_entry = anchor_entry();
     let anchor_address = hdk::commit_entry(&anchor_entry)?;
 
-    let new_course = Course::new(title, AGENT_ADDRESS.to_string().into(), timestamp);
+    let new_course = Course::new(title, AGENT_ADDRESS.to_string().into(), clock::commit_time()?);
     let new_course_entry = new_course.entry();
     let new_course_address = hdk::commit_entry(&new_course_entry)?;
 
@@ -232,17 +448,13 @@ pub fn delete(course_address: Address) -> ZomeApiResult<Address> {
 }
 
 pub fn update(title: String, modules_addresses: Vec<Address>, course_address: Address) -> ZomeApiResult<Address> {
-    let course: Course = hdk::utils::get_as_type(course_address.to_string().into())?;
-    
-    let new_version_course = Course::from(
-        title,
-        course.teacher_address,
-        course.timestamp,
-        modules_addresses,
-    );
-    let new_version_course_entry = new_version_course.entry();
-
-    hdk::update_entry(new_version_course_entry, &course_address)
+    let mut course: Course = hdk::utils::get_as_type(course_address.to_string().into())?;
+    course.title = title;
+    course.modules = modules_addresses;
+
+    course.touch()?;
+
+    hdk::update_entry(course.entry(), &course_address)
 }
 
 pub fn list() -> ZomeApiResult<Vec<Address>> {
@@ -253,18 +465,26 @@ pub fn list() -> ZomeApiResult<Vec<Address>> {
         LinkMatch::Any,
     )?;
 
-    Ok(addresses.addresses())
+    moderation::filter_hidden(addresses.addresses())
 }
 
 pub fn get_my_courses() -> ZomeApiResult<Vec<Address>> {
+    get_teacher_courses(AGENT_ADDRESS.to_string().into())
+}
+
+pub fn get_teacher_courses(teacher_address: Address) -> ZomeApiResult<Vec<Address>> {
     //teacher -> courses
     let links = hdk::get_links(
-        &AGENT_ADDRESS,
+        &teacher_address,
         LinkMatch::Exactly("teacher->courses"),
         LinkMatch::Any,
     )?;
 
-    Ok(links.addresses())
+    // Teachers still see their own hidden courses
+    if teacher_address == Address::from(AGENT_ADDRESS.to_string()) {
+        return Ok(links.addresses());
+    }
+    moderation::filter_hidden(links.addresses())
 }
 
 pub fn get_my_enrolled_courses() -> ZomeApiResult<Vec<Address>> {
@@ -277,9 +497,118 @@ pub fn get_my_enrolled_courses() -> ZomeApiResult<Vec<Address>> {
     Ok(links.addresses())
 }
 
-pub fn enrol_in_course(course_address: Address) -> ZomeApiResult<Address> {
+pub fn enrol_in_course(course_address: Address, cohort_address: Option<Address>) -> ZomeApiResult<Address> {
+    let course: Course = hdk::utils::get_as_type(course_address.clone())?;
+    prerequisite::validate_student_prerequisites(&course, &AGENT_ADDRESS)?;
+    cohort::check_selection(&course_address, &cohort_address)?;
+
+    match course.enrollment_policy {
+        EnrollmentPolicy::Open => (),
+        EnrollmentPolicy::ApprovalRequired => return enrollment::request(&course_address, &cohort_address),
+        EnrollmentPolicy::InviteOnly => {
+            return Err(ZomeApiError::from(String::from("This course is invite only")))
+        }
+    }
+
+    if course.price.is_some() {
+        return Err(ZomeApiError::from(String::from(
+            "This course has a price, purchase it instead",
+        )));
+    }
+
+    if waitlist::is_full(&course, &course_address)? {
+        return waitlist::join(&course_address, &cohort_address);
+    }
+
     hdk::link_entries(&AGENT_ADDRESS, &course_address, "student->courses", "")?;
-    hdk::link_entries(&course_address, &AGENT_ADDRESS, "course->students", "")
+    let link_address = hdk::link_entries(&course_address, &AGENT_ADDRESS, "course->students", "")?;
+    cohort::join(&cohort_address, &AGENT_ADDRESS)?;
+    Ok(link_address)
+}
+
+/// Leaves the course and tells the teacher's node, which moves the contents to
+/// a new key and fills the seat. If the teacher is offline neither happens.
+pub fn unenrol_from_course(course_address: Address) -> ZomeApiResult<Option<Address>> {
+    let student_address: Address = AGENT_ADDRESS.to_string().into();
+    waitlist::leave(&course_address, &student_address)?;
+    if !remove_enrollment(&course_address, &student_address)? {
+        return Ok(None);
+    }
+
+    let course: Course = hdk::utils::get_as_type(course_address.clone())?;
+    let student_left = DirectMessage::StudentLeft {
+        course_address: course_address.clone(),
+    };
+    match message::send(course.teacher_address, student_left) {
+        Ok(promoted) => Ok(promoted),
+        Err(error) => {
+            hdk::debug(format!("Could not tell the teacher a student left: {}", error))?;
+            Ok(None)
+        }
+    }
+}
+
+pub fn remove_student(course_address: Address, student_address: Address) -> ZomeApiResult<Option<Address>> {
+    let course = validate_is_teacher(&course_address)?;
+    if !remove_enrollment(&course_address, &student_address)? {
+        return Ok(None);
+    }
+    free_seat(course_address, &course)
+}
+
+/// Runs on the teacher's node when a student has unenrolled. Any member can
+/// send this, which at worst moves the contents to yet another key.
+pub fn handle_student_left(course_address: Address) -> ZomeApiResult<Option<Address>> {
+    let course = validate_is_teacher(&course_address)?;
+    free_seat(course_address, &course)
+}
+
+fn validate_is_teacher(course_address: &Address) -> ZomeApiResult<Course> {
+    let course: Course = hdk::utils::get_as_type(course_address.clone())?;
+    if course.teacher_address != Address::from(AGENT_ADDRESS.to_string()) {
+        return Err(ZomeApiError::from(String::from(
+            "Only the teacher can manage the students of their course",
+        )));
+    }
+    Ok(course)
+}
+
+/// Drops the enrollment links, returning whether the student was enrolled.
+fn remove_enrollment(course_address: &Address, student_address: &Address) -> ZomeApiResult<bool> {
+    if !get_students(course_address.clone())?.contains(student_address) {
+        return Ok(false);
+    }
+    cohort::leave(course_address, student_address)?;
+    hdk::remove_link(student_address, course_address, "student->courses", "")?;
+    // Paid enrollments are tagged with their payment, so remove every tag found
+    let links = hdk::get_links(
+        course_address,
+        LinkMatch::Exactly("course->students"),
+        LinkMatch::Any,
+    )?;
+    for link in links.links() {
+        if &link.address == student_address {
+            hdk::remove_link(course_address, student_address, "course->students", link.tag.as_str())?;
+        }
+    }
+    Ok(true)
+}
+
+/// Runs on the teacher's node once a student is gone: moves the contents to a
+/// key the student never received, then hands the seat to the waitlist,
+/// returning the promoted student if there was one.
+fn free_seat(course_address: Address, course: &Course) -> ZomeApiResult<Option<Address>> {
+    if course.content_key_version.is_some() {
+        encryption::rotate_key(course_address.clone())?;
+    }
+    waitlist::promote_next(&course_address)
+}
+
+pub fn update_capacity(course_address: Address, max_students: Option<u32>) -> ZomeApiResult<Address> {
+    let mut course: Course = hdk::utils::get_as_type(course_address.clone())?;
+    course.max_students = max_students;
+    course.touch()?;
+    hdk::update_entry(course.entry(), &course_address)
 }
 
 pub fn get_students(course_address: Address) -> ZomeApiResult<Vec<Address>> {
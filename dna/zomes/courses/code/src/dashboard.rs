@@ -0,0 +1,119 @@
+/************************ Import Required Libraries */
+use hdk::prelude::*;
+
+use crate::clock;
+use crate::content;
+use crate::content::Content;
+use crate::course;
+use crate::course::Course;
+use crate::moderation;
+use crate::module::Module;
+use crate::schedule;
+use hdk::holochain_core_types::time::Iso8601;
+use hdk::AGENT_ADDRESS;
+use std::convert::TryFrom;
+/******************************************* */
+
+/// Committed to the student's private chain each time they open their
+/// dashboard. The time of the visit is read from its header.
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct LastSeen {
+    pub visit: usize, // Keeps each visit a distinct entry
+}
+
+impl LastSeen {
+    pub fn entry(&self) -> Entry {
+        Entry::App("last_seen".into(), self.into())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct DashboardCourse {
+    pub course_address: Address,
+    pub title: String,
+    pub teacher_address: Address,
+    pub module_count: u32, // Released modules only
+    pub content_count: u32,
+    pub new_modules: Vec<Address>, // Created since the last visit
+    pub new_contents: Vec<Address>,
+}
+
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct Dashboard {
+    pub last_seen: Option<Iso8601>, // None on the first visit, when nothing counts as new
+    pub courses: Vec<DashboardCourse>,
+}
+
+////////////////////Last Seen Entry Definition
+pub fn entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "last_seen",
+        description: "when the student last opened their dashboard",
+        sharing: Sharing::Private,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: | _validation_data: hdk::EntryValidationData<LastSeen> | {
+            Ok(())
+        }
+    )
+}
+
+/// Helper Functions
+fn is_new(created_at: &Iso8601, last_seen: &Option<Iso8601>) -> bool {
+    match last_seen {
+        Some(last_seen) => created_at > last_seen,
+        None => false,
+    }
+}
+
+fn summarise(course_address: Address, course: Course, last_seen: &Option<Iso8601>, now: &Iso8601) -> ZomeApiResult<DashboardCourse> {
+    let mut summary = DashboardCourse {
+        course_address,
+        title: course.title,
+        teacher_address: course.teacher_address,
+        module_count: 0,
+        content_count: 0,
+        new_modules: Vec::new(),
+        new_contents: Vec::new(),
+    };
+
+    for module_address in course.modules {
+        let module: Module = hdk::utils::get_as_type(module_address.clone())?;
+        if !schedule::is_released(&module, &AGENT_ADDRESS, now)? || moderation::is_hidden(&module_address)? {
+            continue;
+        }
+        summary.module_count += 1;
+        if is_new(&module.created_at, last_seen) {
+            summary.new_modules.push(module_address.clone());
+        }
+
+        for content_address in moderation::filter_hidden(content::get_contents(&module_address)?)? {
+            let content: Content = hdk::utils::get_as_type(content_address.clone())?;
+            summary.content_count += 1;
+            if is_new(content.created_at(), last_seen) {
+                summary.new_contents.push(content_address);
+            }
+        }
+    }
+    Ok(summary)
+}
+
+/// Records this visit, then gathers the student's enrolled courses and what
+/// was added to them since the previous one.
+pub fn get_my_dashboard() -> ZomeApiResult<Dashboard> {
+    let visits = clock::commit_times("last_seen")?;
+    let last_seen = visits.iter().max().cloned();
+    hdk::commit_entry(&LastSeen { visit: visits.len() }.entry())?;
+    let now = clock::now()?;
+
+    let mut courses = Vec::new();
+    for course_address in course::get_my_enrolled_courses()? {
+        // Deleted courses keep their enrollment links but no longer load
+        if let Ok(course) = hdk::utils::get_as_type::<Course>(course_address.clone()) {
+            courses.push(summarise(course_address, course, &last_seen, &now)?);
+        }
+    }
+
+    Ok(Dashboard { last_seen, courses })
+}
@@ -0,0 +1,357 @@
+/************************ Import Required Libraries */
+use hdk::prelude::*;
+
+use crate::content;
+use crate::content::Content;
+use crate::course;
+use crate::course::Course;
+use crate::invite;
+use crate::message;
+use crate::message::DirectMessage;
+use crate::transfer;
+use chacha20poly1305::aead::generic_array::GenericArray;
+use chacha20poly1305::aead::{Aead, NewAead, Payload};
+use chacha20poly1305::XChaCha20Poly1305;
+use hdk::AGENT_ADDRESS;
+use holochain_wasm_utils::api_serialization::QueryArgsNames;
+use sha2::{Digest, Sha256};
+use std::convert::TryFrom;
+/******************************************* */
+
+const NONCE_SIZE: usize = 24;
+const TAG_SIZE: usize = 16;
+
+/// A course key received from the teacher's node, who derived it. Kept on
+/// the receiver's own chain only.
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct CourseKey {
+    pub course_address: Address,
+    pub teacher_address: Address, // Two teachers can derive keys of the same version, so keys are told apart by who derived them
+    pub key_version: u32,
+    key: String,
+}
+
+impl CourseKey {
+    pub fn new(course_address: Address, teacher_address: Address, key_version: u32, key: String) -> Self {
+        CourseKey {
+            course_address,
+            teacher_address,
+            key_version,
+            key,
+        }
+    }
+
+    pub fn entry(&self) -> Entry {
+        Entry::App("course_key".into(), self.into())
+    }
+}
+
+////////////////////Course Key Entry Definition
+pub fn entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "course_key",
+        description: "a key to decrypt the contents of a course, received from its teacher",
+        sharing: Sharing::Private,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: | _validation_data: hdk::EntryValidationData<CourseKey> | {
+            Ok(())
+        }
+    )
+}
+
+/*********************** Cipher */
+type Key = [u8; 32];
+
+/// XChaCha20-Poly1305 from the `chacha20poly1305` crate. A field is stored as
+/// base64 of nonce || ciphertext || tag; its nonces are long enough to be
+/// picked at random.
+fn cipher(key: &Key) -> XChaCha20Poly1305 {
+    XChaCha20Poly1305::new(GenericArray::clone_from_slice(key))
+}
+
+/// Zomes have no random number generator, but every one-time signature is
+/// made with a fresh key pair from the conductor, so its public key is random.
+fn random_nonce() -> ZomeApiResult<Vec<u8>> {
+    let one_time = hdk::sign_one_time(vec!["course-content-nonce"])?;
+    let mut nonce = Sha256::digest(one_time.pub_key.as_bytes()).to_vec();
+    nonce.truncate(NONCE_SIZE);
+    Ok(nonce)
+}
+
+/// `associated_data` is authenticated but not encrypted: it ties the
+/// ciphertext to the field it was sealed for.
+pub fn encrypt_field(key: &Key, associated_data: &str, plaintext: &str) -> ZomeApiResult<String> {
+    let nonce = random_nonce()?;
+    let payload = Payload {
+        msg: plaintext.as_bytes(),
+        aad: associated_data.as_bytes(),
+    };
+    let data = cipher(key)
+        .encrypt(GenericArray::from_slice(&nonce), payload)
+        .map_err(|_| ZomeApiError::from(String::from("Could not encrypt content")))?;
+
+    let mut sealed = nonce;
+    sealed.extend(data);
+    Ok(base64::encode(&sealed))
+}
+
+pub fn decrypt_field(key: &Key, associated_data: &str, ciphertext: &str) -> ZomeApiResult<String> {
+    let sealed = base64::decode(ciphertext)
+        .map_err(|_| ZomeApiError::from(String::from("Content is not correctly encrypted")))?;
+    if sealed.len() < NONCE_SIZE + TAG_SIZE {
+        return Err(ZomeApiError::from(String::from("Content is not correctly encrypted")));
+    }
+
+    let (nonce, data) = sealed.split_at(NONCE_SIZE);
+    let payload = Payload {
+        msg: data,
+        aad: associated_data.as_bytes(),
+    };
+    let data = cipher(key)
+        .decrypt(GenericArray::from_slice(nonce), payload)
+        .map_err(|_| ZomeApiError::from(String::from("Could not decrypt content")))?;
+    String::from_utf8(data).map_err(|_| ZomeApiError::from(String::from("Could not decrypt content")))
+}
+
+/*********************** Keys */
+fn encode_key(key: &Key) -> String {
+    base64::encode(key)
+}
+
+fn decode_key(key: &str) -> ZomeApiResult<Key> {
+    let bytes = base64::decode(key).map_err(|_| ZomeApiError::from(String::from("Invalid course key")))?;
+    if bytes.len() != 32 {
+        return Err(ZomeApiError::from(String::from("Invalid course key")));
+    }
+    let mut decoded = [0u8; 32];
+    decoded.copy_from_slice(&bytes);
+    Ok(decoded)
+}
+
+/// Only the teacher's node can derive a course key: it comes from the
+/// teacher's signature, so nothing needs to be stored on their side.
+fn derive_key(course_address: &Address, key_version: u32) -> ZomeApiResult<Key> {
+    let signature = hdk::sign(format!("course-key:{}:{}", course_address, key_version))?;
+    let mut key = [0u8; 32];
+    key.copy_from_slice(&Sha256::digest(signature.as_bytes()));
+    Ok(key)
+}
+
+fn get_stored_key(course_address: &Address, teacher_address: &Address, key_version: u32) -> ZomeApiResult<Option<Key>> {
+    let result = hdk::query_result(
+        QueryArgsNames::QueryName("course_key".into()),
+        QueryArgsOptions {
+            start: 0,
+            limit: 0,
+            headers: false,
+            entries: true,
+        },
+    )?;
+
+    let stored = match result {
+        QueryResult::Entries(entries) => entries
+            .into_iter()
+            .filter_map(|(_, entry)| match entry {
+                Entry::App(_, value) => CourseKey::try_from(value).ok(),
+                _ => None,
+            })
+            .find(|course_key| {
+                &course_key.course_address == course_address
+                    && &course_key.teacher_address == teacher_address
+                    && course_key.key_version == key_version
+            }),
+        _ => None,
+    };
+
+    match stored {
+        Some(course_key) => Ok(Some(decode_key(&course_key.key)?)),
+        None => Ok(None),
+    }
+}
+
+fn is_teacher(course: &Course) -> bool {
+    course.teacher_address == Address::from(AGENT_ADDRESS.to_string())
+}
+
+pub fn get_key(course_address: &Address, course: &Course, key_version: u32) -> ZomeApiResult<Key> {
+    if is_teacher(course) {
+        return derive_key(course_address, key_version);
+    }
+    if let Some(key) = get_stored_key(course_address, &course.teacher_address, key_version)? {
+        return Ok(key);
+    }
+
+    let token = match course.private {
+        true => invite::get_my_access_token(course_address).ok(),
+        false => None,
+    };
+    let key: String = message::send(
+        course.teacher_address.clone(),
+        DirectMessage::KeyRequest {
+            course_address: course_address.clone(),
+            key_version,
+            token,
+        },
+    )?;
+    hdk::commit_entry(&CourseKey::new(course_address.clone(), course.teacher_address.clone(), key_version, key.clone()).entry())?;
+    decode_key(&key)
+}
+
+/// Runs on the teacher's node: only the current key is handed out, to the
+/// students enrolled now or to the member a transfer of the course was
+/// offered to. Students of a private course also need a live access grant.
+pub fn handle_key_request(
+    agent_address: &Address,
+    course_address: Address,
+    key_version: u32,
+    token: Option<Address>,
+) -> ZomeApiResult<String> {
+    let course: Course = hdk::utils::get_as_type(course_address.clone())?;
+    if !is_teacher(&course) {
+        return Err(ZomeApiError::from(String::from(
+            "Only the teacher can hand out the course key",
+        )));
+    }
+    if course.content_key_version != Some(key_version) {
+        return Err(ZomeApiError::from(String::from(
+            "Only the current course key is handed out",
+        )));
+    }
+    if transfer::is_offered_to(&course_address, agent_address)? {
+        return Ok(encode_key(&derive_key(&course_address, key_version)?));
+    }
+    if !course::get_students(course_address.clone())?.contains(agent_address) {
+        return Err(ZomeApiError::from(String::from(
+            "Only enrolled students can get the course key",
+        )));
+    }
+    if course.private {
+        let token = token.ok_or_else(|| ZomeApiError::from(String::from("No valid access grant for this course")))?;
+        invite::validate_access_token(agent_address, &course_address, &token)?;
+    }
+    Ok(encode_key(&derive_key(&course_address, key_version)?))
+}
+
+/// Runs on a student's node when the teacher pushes a rotated key.
+pub fn handle_course_key(teacher_address: &Address, course_address: Address, key_version: u32, key: String) -> ZomeApiResult<Address> {
+    let course: Course = hdk::utils::get_as_type(course_address.clone())?;
+    if &course.teacher_address != teacher_address {
+        return Err(ZomeApiError::from(String::from(
+            "Only the teacher can distribute the course key",
+        )));
+    }
+    decode_key(&key)?;
+    hdk::commit_entry(&CourseKey::new(course_address, teacher_address.clone(), key_version, key).entry())
+}
+
+/*********************** Content */
+pub fn encrypt_content(course_address: &Address, course: &Course, content: Content) -> ZomeApiResult<Content> {
+    match course.content_key_version {
+        Some(key_version) => content.encrypt(key_version, &derive_key(course_address, key_version)?),
+        None => Ok(content),
+    }
+}
+
+pub fn decrypt_content(course_address: &Address, course: &Course, content: Content) -> ZomeApiResult<Content> {
+    match content.key_version() {
+        Some(key_version) => content.decrypt(&get_key(course_address, course, key_version)?),
+        None => Ok(content),
+    }
+}
+
+/// Applies `change` to the course in a single update, then re-encrypts every
+/// content if the change moved them to another key or in or out of the clear.
+pub fn update_sealed<F: FnOnce(&mut Course)>(course_address: &Address, change: F) -> ZomeApiResult<Address> {
+    let mut course: Course = hdk::utils::get_as_type(course_address.clone())?;
+    let previous = course.clone();
+    change(&mut course);
+    course.touch()?;
+    let updated_address = hdk::update_entry(course.entry(), course_address)?;
+    if course.content_key_version == previous.content_key_version {
+        return Ok(updated_address);
+    }
+
+    for module_address in &course.modules {
+        for content_address in content::get_contents(module_address)? {
+            let stored: Content = hdk::utils::get_as_type(content_address.clone())?;
+            let plain = decrypt_content(course_address, &previous, stored)?;
+            let mut sealed = encrypt_content(course_address, &course, plain)?;
+            sealed.touch()?;
+            hdk::update_entry(sealed.entry(), &content_address)?;
+        }
+    }
+    Ok(updated_address)
+}
+
+/// Re-encrypts every content of the course with `key_version`, or stores it
+/// in clear when `None`. Used to switch encryption on/off and to rotate keys.
+fn set_key_version(course_address: &Address, key_version: Option<u32>) -> ZomeApiResult<Address> {
+    update_sealed(course_address, |course| course.content_key_version = key_version)
+}
+
+pub fn update_encryption(course_address: Address, encrypted: bool) -> ZomeApiResult<Address> {
+    let course: Course = hdk::utils::get_as_type(course_address.clone())?;
+    if course.private && !encrypted {
+        return Err(ZomeApiError::from(String::from(
+            "Private courses keep their contents encrypted",
+        )));
+    }
+    let key_version = match (encrypted, course.content_key_version) {
+        (true, Some(key_version)) => Some(key_version),
+        (true, None) => Some(1),
+        (false, _) => None,
+    };
+    set_key_version(&course_address, key_version)
+}
+
+/// Moves the course to a new key the removed students never received,
+/// and pushes it to the students that remain.
+pub fn rotate_key(course_address: Address) -> ZomeApiResult<Address> {
+    let course: Course = hdk::utils::get_as_type(course_address.clone())?;
+    let key_version = match course.content_key_version {
+        Some(key_version) => key_version + 1,
+        None => return Err(ZomeApiError::from(String::from("Course content is not encrypted"))),
+    };
+    let updated_address = set_key_version(&course_address, Some(key_version))?;
+    distribute_key(&course_address, key_version)?;
+    Ok(updated_address)
+}
+
+/// Runs on the new teacher's node when they accept a transfer. Keys come from
+/// the teacher's signature, so the new teacher takes the current key over from
+/// the previous one, moves the contents to a key of their own and pushes it to
+/// the students. The previous teacher's node must be online to hand over the
+/// key of an encrypted course, or accepting fails and can be retried later.
+pub fn hand_over(course_address: &Address, new_teacher_address: &Address) -> ZomeApiResult<Address> {
+    let course: Course = hdk::utils::get_as_type(course_address.clone())?;
+    let key_version = match course.content_key_version {
+        Some(key_version) => key_version,
+        None => return update_sealed(course_address, |course| course.teacher_address = new_teacher_address.clone()),
+    };
+
+    // stored for the previous teacher, so the contents can be opened while resealing
+    get_key(course_address, &course, key_version)?;
+    let updated_address = update_sealed(course_address, |course| {
+        course.teacher_address = new_teacher_address.clone();
+        course.content_key_version = Some(key_version + 1);
+    })?;
+    distribute_key(course_address, key_version + 1)?;
+    Ok(updated_address)
+}
+
+fn distribute_key(course_address: &Address, key_version: u32) -> ZomeApiResult<()> {
+    let key = encode_key(&derive_key(course_address, key_version)?);
+    for student_address in course::get_students(course_address.clone())? {
+        let course_key = DirectMessage::CourseKey {
+            course_address: course_address.clone(),
+            key_version,
+            key: key.clone(),
+        };
+        if let Err(error) = message::send::<serde_json::Value>(student_address, course_key) {
+            hdk::debug(format!("Could not send rotated course key: {}", error))?;
+        }
+    }
+    Ok(())
+}
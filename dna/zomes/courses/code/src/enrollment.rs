@@ -0,0 +1,252 @@
+/************************ Import Required Libraries */
+use hdk::prelude::*;
+
+use crate::cohort;
+use crate::course::Course;
+use crate::waitlist;
+use hdk::AGENT_ADDRESS;
+use std::convert::TryFrom;
+/******************************************* */
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum EnrollmentPolicy {
+    Open,
+    ApprovalRequired,
+    InviteOnly,
+}
+
+impl Default for EnrollmentPolicy {
+    fn default() -> Self {
+        EnrollmentPolicy::Open
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum EnrollmentStatus {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct EnrollmentRequest {
+    pub course_address: Address,
+    pub student_address: Address,
+    pub cohort_address: Option<Address>,
+    pub status: EnrollmentStatus,
+}
+
+impl EnrollmentRequest {
+    pub fn new(course_address: Address, student_address: Address, cohort_address: Option<Address>, status: EnrollmentStatus) -> Self {
+        EnrollmentRequest {
+            course_address,
+            student_address,
+            cohort_address,
+            status,
+        }
+    }
+
+    pub fn entry(&self) -> Entry {
+        Entry::App("enrollment_request".into(), self.into())
+    }
+}
+
+////////////////////Enrollment Request Entry Definition
+pub fn entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "enrollment_request",
+        description: "a student's request to join a course that requires the teacher's approval",
+        sharing: Sharing::Public,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: | validation_data: hdk::EntryValidationData<EnrollmentRequest> | {
+            match validation_data {
+                EntryValidationData::Create {entry, validation_data} => {
+                    if !validation_data.sources().contains(&entry.student_address) {
+                        return Err(String::from("Only the student can request their enrollment"));
+                    }
+                    if entry.status != EnrollmentStatus::Pending {
+                        return Err(String::from("A new enrollment request must be pending"));
+                    }
+                    Ok(())
+                },
+                EntryValidationData::Modify {new_entry, old_entry, validation_data, ..} => {
+                    if new_entry.course_address != old_entry.course_address
+                        || new_entry.student_address != old_entry.student_address
+                        || new_entry.cohort_address != old_entry.cohort_address {
+                        return Err(String::from("Cannot change the course, student or cohort of an enrollment request"));
+                    }
+                    if old_entry.status != EnrollmentStatus::Pending {
+                        return Err(String::from("This enrollment request has already been answered"));
+                    }
+                    validate_teacher(&validation_data.sources(), &new_entry.course_address)?;
+                    if new_entry.status == EnrollmentStatus::Approved {
+                        validate_approval_policy(&new_entry.course_address)?;
+                    }
+                    Ok(())
+                },
+                EntryValidationData::Delete {old_entry, validation_data, ..} => {
+                    if validation_data.sources().contains(&old_entry.student_address) {
+                        return Ok(());
+                    }
+                    validate_teacher(&validation_data.sources(), &old_entry.course_address)?;
+                    Ok(())
+                }
+            }
+        },
+        links: [
+            from!( // pending requests the teacher has to answer
+                "course",
+                link_type: "course->enrollment_requests",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | _validation_data: hdk::LinkValidationData | {
+                    Ok(())
+                }
+            )
+        ]
+    )
+}
+
+/*********************** Enrollment Validations */
+fn validate_teacher(signing_addresses: &Vec<Address>, course_address: &Address) -> ZomeApiResult<()> {
+    let course: Course = hdk::utils::get_as_type(course_address.clone())?;
+    if !signing_addresses.contains(&course.teacher_address) {
+        return Err(ZomeApiError::from(String::from(
+            "Only the teacher can answer an enrollment request",
+        )));
+    }
+    Ok(())
+}
+
+fn validate_approval_policy(course_address: &Address) -> ZomeApiResult<()> {
+    let course: Course = hdk::utils::get_as_type(course_address.clone())?;
+    if course.enrollment_policy != EnrollmentPolicy::ApprovalRequired {
+        return Err(ZomeApiError::from(String::from(
+            "This course does not take enrollment requests",
+        )));
+    }
+    Ok(())
+}
+
+/// Called from the `course->students` link validation: unless the course is
+/// open, the link must come from the teacher (e.g. a redeemed invite). Only
+/// while the course requires approval may the student instead hold a request
+/// the teacher has approved; approvals stop counting once it is invite only.
+pub fn validate_enrollment(
+    course_address: &Address,
+    student_address: &Address,
+    signing_addresses: &Vec<Address>,
+) -> ZomeApiResult<()> {
+    let course: Course = hdk::utils::get_as_type(course_address.clone())?;
+    if course.enrollment_policy == EnrollmentPolicy::Open || signing_addresses.contains(&course.teacher_address) {
+        return Ok(());
+    }
+    if course.enrollment_policy == EnrollmentPolicy::InviteOnly {
+        return Err(ZomeApiError::from(String::from("This course is invite only")));
+    }
+    if !is_approved(course_address, student_address)? {
+        return Err(ZomeApiError::from(String::from(
+            "Enrollment has not been approved by the teacher",
+        )));
+    }
+    Ok(())
+}
+
+/// The approved request may name any of the course's cohorts, or none.
+pub fn is_approved(course_address: &Address, student_address: &Address) -> ZomeApiResult<bool> {
+    let mut cohort_addresses: Vec<Option<Address>> = vec![None];
+    cohort_addresses.extend(cohort::get_cohorts(course_address.clone())?.into_iter().map(Some));
+
+    for cohort_address in cohort_addresses {
+        if is_approved_for(course_address, student_address, &cohort_address)? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Whether the teacher approved the student's request for this cohort.
+pub fn is_approved_for(course_address: &Address, student_address: &Address, cohort_address: &Option<Address>) -> ZomeApiResult<bool> {
+    let approved = EnrollmentRequest::new(
+        course_address.clone(),
+        student_address.clone(),
+        cohort_address.clone(),
+        EnrollmentStatus::Approved,
+    );
+    Ok(hdk::get_entry(&hdk::entry_address(&approved.entry())?)?.is_some())
+}
+
+/// Helper Functions
+pub fn request(course_address: &Address, cohort_address: &Option<Address>) -> ZomeApiResult<Address> {
+    let request = EnrollmentRequest::new(
+        course_address.clone(),
+        AGENT_ADDRESS.to_string().into(),
+        cohort_address.clone(),
+        EnrollmentStatus::Pending,
+    );
+    let request_address = hdk::commit_entry(&request.entry())?;
+    hdk::link_entries(course_address, &request_address, "course->enrollment_requests", "")?;
+    Ok(request_address)
+}
+
+pub fn get_enrollment_requests(course_address: Address) -> ZomeApiResult<Vec<Address>> {
+    let links = hdk::get_links(
+        &course_address,
+        LinkMatch::Exactly("course->enrollment_requests"),
+        LinkMatch::Any,
+    )?;
+    Ok(links.addresses())
+}
+
+fn answer(request_address: &Address, status: EnrollmentStatus) -> ZomeApiResult<EnrollmentRequest> {
+    let mut request: EnrollmentRequest = hdk::utils::get_as_type(request_address.clone())?;
+    request.status = status;
+    hdk::update_entry(request.entry(), request_address)?;
+    hdk::remove_link(&request.course_address, request_address, "course->enrollment_requests", "")?;
+    Ok(request)
+}
+
+/// Everything that could still refuse the student is checked before the
+/// request is answered, so an approved request always comes with a seat.
+pub fn approve(request_address: Address) -> ZomeApiResult<Address> {
+    let request: EnrollmentRequest = hdk::utils::get_as_type(request_address.clone())?;
+    let course: Course = hdk::utils::get_as_type(request.course_address.clone())?;
+    if request.status != EnrollmentStatus::Pending {
+        return Err(ZomeApiError::from(String::from(
+            "This enrollment request has already been answered",
+        )));
+    }
+    validate_approval_policy(&request.course_address)?;
+    if waitlist::is_full(&course, &request.course_address)? {
+        return Err(ZomeApiError::from(String::from("Course is full")));
+    }
+    cohort::check_selection(&request.course_address, &request.cohort_address)?;
+
+    let request = answer(&request_address, EnrollmentStatus::Approved)?;
+    if course.price.is_some() {
+        // the student takes the seat by purchasing the course
+        return hdk::entry_address(&request.entry());
+    }
+    hdk::link_entries(&request.student_address, &request.course_address, "student->courses", "")?;
+    let link_address = hdk::link_entries(&request.course_address, &request.student_address, "course->students", "")?;
+    cohort::join(&request.cohort_address, &request.student_address)?;
+    Ok(link_address)
+}
+
+pub fn reject(request_address: Address) -> ZomeApiResult<Address> {
+    let request = answer(&request_address, EnrollmentStatus::Rejected)?;
+    hdk::entry_address(&request.entry())
+}
+
+pub fn update_policy(course_address: Address, enrollment_policy: EnrollmentPolicy) -> ZomeApiResult<Address> {
+    let mut course: Course = hdk::utils::get_as_type(course_address.clone())?;
+    if course.private && enrollment_policy != EnrollmentPolicy::InviteOnly {
+        return Err(ZomeApiError::from(String::from("Private courses are invite only")));
+    }
+    course.enrollment_policy = enrollment_policy;
+    course.touch()?;
+    hdk::update_entry(course.entry(), &course_address)
+}
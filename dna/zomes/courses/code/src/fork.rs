@@ -0,0 +1,117 @@
+/************************ Import Required Libraries */
+use hdk::prelude::*;
+
+use crate::bundle;
+use crate::bundle::{ContentRecord, ImportReport, ModuleRecord};
+use crate::content;
+use crate::content::Content;
+use crate::course;
+use crate::course::Course;
+use crate::encryption;
+use crate::moderation;
+use crate::module::Module;
+use crate::schedule;
+use hdk::AGENT_ADDRESS;
+/******************************************* */
+
+/*********************** Fork Validations */
+
+/// Teachers can always clone their own courses; other courses only while
+/// their teacher allows it. This only holds for clones that record where they
+/// came from: a client that copies the contents into a new course without the
+/// `forked_from` link cannot be told apart from a course written from scratch.
+pub fn validate_fork(course_address: &Address, source_address: &Address, signing_addresses: &Vec<Address>) -> ZomeApiResult<()> {
+    let course: Course = hdk::utils::get_as_type(course_address.clone())?;
+    if !signing_addresses.contains(&course.teacher_address) {
+        return Err(ZomeApiError::from(String::from(
+            "Only the teacher of the clone can record where it came from",
+        )));
+    }
+    let source: Course = hdk::utils::get_as_type(source_address.clone())?;
+    if !source.forkable && source.teacher_address != course.teacher_address {
+        return Err(ZomeApiError::from(String::from(
+            "The teacher of this course does not allow cloning it",
+        )));
+    }
+    Ok(())
+}
+
+/// Helper Functions
+
+/// Reads the stored contents directly rather than through `content::get`, so
+/// cloning does not count as a view. Contents taken down by a moderator are
+/// left out unless the source's own teacher is cloning.
+fn copy_contents(source_address: &Address, source: &Course, module_address: &Address, is_teacher: bool) -> ZomeApiResult<Vec<ContentRecord>> {
+    let mut content_addresses = content::get_contents(module_address)?;
+    if !is_teacher {
+        content_addresses = moderation::filter_hidden(content_addresses)?;
+    }
+    let mut contents = Vec::new();
+    for content_address in content_addresses {
+        let stored: Content = hdk::utils::get_as_type(content_address.clone())?;
+        contents.push((content_address, encryption::decrypt_content(source_address, source, stored)?));
+    }
+    Ok(bundle::content_records(contents))
+}
+
+pub fn clone_course(source_address: Address, new_title: String) -> ZomeApiResult<ImportReport> {
+    let source: Course = hdk::utils::get_as_type(source_address.clone())?;
+    let is_teacher = source.teacher_address == Address::from(AGENT_ADDRESS.to_string());
+    if !source.forkable && !is_teacher {
+        return Err(ZomeApiError::from(String::from(
+            "The teacher of this course does not allow cloning it",
+        )));
+    }
+    if (source.private || source.content_key_version.is_some()) && !is_teacher {
+        return Err(ZomeApiError::from(String::from(
+            "Private and encrypted courses can only be cloned by their teacher",
+        )));
+    }
+    moderation::validate_visible(&source, &AGENT_ADDRESS, &[&source_address])?;
+    course::validate_course_title(&new_title).map_err(ZomeApiError::from)?;
+    course::validate_teacher_role(&AGENT_ADDRESS)?;
+
+    // Others only get the modules they could read themselves
+    let module_addresses = if is_teacher {
+        source.modules.clone()
+    } else {
+        moderation::filter_hidden(schedule::released_modules(&source, &AGENT_ADDRESS)?)?
+    };
+    let mut modules = Vec::new();
+    for module_address in &module_addresses {
+        let module: Module = hdk::utils::get_as_type(module_address.clone())?;
+        modules.push(ModuleRecord {
+            source_address: module_address.clone(),
+            title: module.title,
+            release_offset: module.release_offset,
+            release_at: module.release_at,
+            contents: copy_contents(&source_address, &source, module_address, is_teacher)?,
+        });
+    }
+
+    // the clone is sealed like its source, as the copies are taken in clear
+    let report = bundle::create_course_from(
+        new_title,
+        modules,
+        source.private,
+        source.content_key_version.is_some(),
+    )?;
+    hdk::link_entries(&report.course_address, &source_address, "course->forked_from", "")?;
+    Ok(report)
+}
+
+pub fn get_forked_from(course_address: Address) -> ZomeApiResult<Option<Address>> {
+    let links = hdk::get_links(
+        &course_address,
+        LinkMatch::Exactly("course->forked_from"),
+        LinkMatch::Any,
+    )?;
+    Ok(links.addresses().into_iter().next())
+}
+
+pub fn update_forking(course_address: Address, forkable: bool) -> ZomeApiResult<Address> {
+    let mut course: Course = hdk::utils::get_as_type(course_address.clone())?;
+    course.forkable = forkable;
+    course.touch()?;
+    hdk::update_entry(course.entry(), &course_address)
+}
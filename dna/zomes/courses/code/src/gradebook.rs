@@ -0,0 +1,461 @@
+/************************ Import Required Libraries */
+use hdk::prelude::*;
+
+use crate::course;
+use crate::course::Course;
+use crate::message;
+use crate::message::DirectMessage;
+use crate::module::Module;
+use hdk::AGENT_ADDRESS;
+use holochain_wasm_utils::api_serialization::QueryArgsNames;
+use std::convert::TryFrom;
+/******************************************* */
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GradeCategory {
+    pub name: String,
+    pub weight: u32,
+}
+
+/// The categories grades fall into (e.g. quizzes, assignments) and how much
+/// each one counts towards a student's total.
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct GradingScheme {
+    pub course_address: Address,
+    pub categories: Vec<GradeCategory>,
+}
+
+impl GradingScheme {
+    pub fn new(course_address: Address, categories: Vec<GradeCategory>) -> Self {
+        GradingScheme {
+            course_address,
+            categories,
+        }
+    }
+
+    pub fn entry(&self) -> Entry {
+        Entry::App("grading_scheme".into(), self.into())
+    }
+}
+
+/// A score the teacher gave a student for one category of a module. Kept on
+/// the teacher's chain only; students get their own grades from the teacher's
+/// node. A new score is a new item with the next `revision`. Grades are not
+/// handed over when the course is transferred: they stay with the teacher who
+/// gave them, and the new teacher starts with an empty gradebook.
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct GradeItem {
+    pub course_address: Address,
+    pub module_address: Address,
+    pub student_address: Address,
+    pub category: String,
+    pub score: u32,
+    pub max_score: u32,
+    pub revision: u32,
+}
+
+impl GradeItem {
+    pub fn new(
+        course_address: Address,
+        module_address: Address,
+        student_address: Address,
+        category: String,
+        score: u32,
+        max_score: u32,
+        revision: u32,
+    ) -> Self {
+        GradeItem {
+            course_address,
+            module_address,
+            student_address,
+            category,
+            score,
+            max_score,
+            revision,
+        }
+    }
+
+    fn grades_same_work(&self, other: &GradeItem) -> bool {
+        self.course_address == other.course_address
+            && self.module_address == other.module_address
+            && self.student_address == other.student_address
+            && self.category == other.category
+    }
+
+    pub fn entry(&self) -> Entry {
+        Entry::App("grade_item".into(), self.into())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct CategoryScore {
+    pub category: String,
+    pub percentage: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct StudentGrades {
+    pub student_address: Address,
+    pub items: Vec<GradeItem>,
+    pub categories: Vec<CategoryScore>,
+    pub total: Option<f64>, // Weighted percentage, None until the student has a grade
+}
+
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct Gradebook {
+    pub course_address: Address,
+    pub categories: Vec<GradeCategory>,
+    pub students: Vec<StudentGrades>,
+}
+
+////////////////////Grading Scheme Entry Definition
+pub fn grading_scheme_entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "grading_scheme",
+        description: "the weighted grade categories of a course",
+        sharing: Sharing::Public,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: | validation_data: hdk::EntryValidationData<GradingScheme> | {
+            match validation_data {
+                EntryValidationData::Create {entry, validation_data} => {
+                    validate_teacher(&validation_data.sources(), &entry.course_address)?;
+                    validate_categories(&entry.categories)
+                },
+                EntryValidationData::Modify {new_entry, old_entry, validation_data, ..} => {
+                    if new_entry.course_address != old_entry.course_address {
+                        return Err(String::from("Cannot move a grading scheme to another course"));
+                    }
+                    validate_teacher(&validation_data.sources(), &old_entry.course_address)?;
+                    validate_categories(&new_entry.categories)
+                },
+                EntryValidationData::Delete {old_entry, validation_data, ..} => {
+                    validate_teacher(&validation_data.sources(), &old_entry.course_address)?;
+                    Ok(())
+                }
+            }
+        },
+        links: [
+            from!(
+                "course",
+                link_type: "course->grading_scheme",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | _validation_data: hdk::LinkValidationData | {
+                    Ok(())
+                }
+            )
+        ]
+    )
+}
+
+////////////////////Grade Item Entry Definition
+pub fn grade_item_entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "grade_item",
+        description: "a score the teacher gave a student for a module",
+        sharing: Sharing::Private,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: | validation_data: hdk::EntryValidationData<GradeItem> | {
+            match validation_data {
+                EntryValidationData::Create {entry, validation_data} => {
+                    validate_teacher(&validation_data.sources(), &entry.course_address)?;
+                    validate_grade(&entry)
+                },
+                _ => Err(String::from("Grades cannot be modified or deleted, give a new score instead"))
+            }
+        }
+    )
+}
+
+/*********************** Gradebook Validations */
+fn validate_teacher(signing_addresses: &Vec<Address>, course_address: &Address) -> ZomeApiResult<()> {
+    let course: Course = hdk::utils::get_as_type(course_address.clone())?;
+    if !signing_addresses.contains(&course.teacher_address) {
+        return Err(ZomeApiError::from(String::from(
+            "Only the teacher can grade their course",
+        )));
+    }
+    Ok(())
+}
+
+fn validate_categories(categories: &Vec<GradeCategory>) -> Result<(), String> {
+    if categories.is_empty() {
+        return Err(String::from("A grading scheme needs at least one category"));
+    }
+    for (index, category) in categories.iter().enumerate() {
+        if category.name.is_empty() {
+            return Err(String::from("A grade category needs a name"));
+        }
+        if categories[..index].iter().any(|other| other.name == category.name) {
+            return Err(format!("Grade category {} is defined twice", category.name));
+        }
+    }
+    if categories.iter().all(|category| category.weight == 0) {
+        return Err(String::from("At least one grade category needs a weight"));
+    }
+    Ok(())
+}
+
+fn validate_grade(grade: &GradeItem) -> ZomeApiResult<()> {
+    if grade.max_score == 0 || grade.score > grade.max_score {
+        return Err(ZomeApiError::from(String::from(
+            "A score must be between zero and a positive maximum",
+        )));
+    }
+    let module: Module = hdk::utils::get_as_type(grade.module_address.clone())?;
+    if module.course_address != grade.course_address {
+        return Err(ZomeApiError::from(String::from("This module is not part of the course")));
+    }
+    if let Some(scheme) = get_scheme(&grade.course_address)? {
+        if !scheme.categories.iter().any(|category| category.name == grade.category) {
+            return Err(ZomeApiError::from(format!("Unknown grade category {}", grade.category)));
+        }
+    }
+    Ok(())
+}
+
+/// Helper Functions
+fn is_teacher(course_address: &Address) -> ZomeApiResult<bool> {
+    let course: Course = hdk::utils::get_as_type(course_address.clone())?;
+    Ok(course.teacher_address == Address::from(AGENT_ADDRESS.to_string()))
+}
+
+fn get_scheme_address(course_address: &Address) -> ZomeApiResult<Option<Address>> {
+    let links = hdk::get_links(
+        course_address,
+        LinkMatch::Exactly("course->grading_scheme"),
+        LinkMatch::Any,
+    )?;
+    Ok(links.addresses().into_iter().next())
+}
+
+fn get_scheme(course_address: &Address) -> ZomeApiResult<Option<GradingScheme>> {
+    match get_scheme_address(course_address)? {
+        Some(scheme_address) => Ok(Some(hdk::utils::get_as_type(scheme_address)?)),
+        None => Ok(None),
+    }
+}
+
+/// The latest revision of each grade the caller gave in the course.
+fn get_grade_items(course_address: &Address) -> ZomeApiResult<Vec<GradeItem>> {
+    let result = hdk::query_result(
+        QueryArgsNames::QueryName("grade_item".into()),
+        QueryArgsOptions {
+            start: 0,
+            limit: 0,
+            headers: false,
+            entries: true,
+        },
+    )?;
+    let committed: Vec<GradeItem> = match result {
+        QueryResult::Entries(entries) => entries
+            .into_iter()
+            .filter_map(|(_, entry)| match entry {
+                Entry::App(_, value) => GradeItem::try_from(value).ok(),
+                _ => None,
+            })
+            .filter(|item| &item.course_address == course_address)
+            .collect(),
+        _ => return Err(ZomeApiError::from(String::from("Unexpected query result"))),
+    };
+
+    let mut latest: Vec<GradeItem> = Vec::new();
+    for item in committed {
+        match latest.iter_mut().find(|other| other.grades_same_work(&item)) {
+            Some(other) if other.revision < item.revision => *other = item,
+            Some(_) => (),
+            None => latest.push(item),
+        }
+    }
+    Ok(latest)
+}
+
+fn get_student_grade_items(course_address: &Address, student_address: &Address) -> ZomeApiResult<Vec<GradeItem>> {
+    Ok(get_grade_items(course_address)?
+        .into_iter()
+        .filter(|item| &item.student_address == student_address)
+        .collect())
+}
+
+pub fn update_grading_scheme(course_address: Address, categories: Vec<GradeCategory>) -> ZomeApiResult<Address> {
+    let scheme = GradingScheme::new(course_address.clone(), categories);
+    match get_scheme_address(&course_address)? {
+        Some(scheme_address) => hdk::update_entry(scheme.entry(), &scheme_address),
+        None => {
+            let scheme_address = hdk::commit_entry(&scheme.entry())?;
+            hdk::link_entries(&course_address, &scheme_address, "course->grading_scheme", "")?;
+            Ok(scheme_address)
+        }
+    }
+}
+
+/// Records a score, replacing the one the student already had for the same
+/// module and category.
+pub fn grade_student(
+    module_address: Address,
+    student_address: Address,
+    category: String,
+    score: u32,
+    max_score: u32,
+) -> ZomeApiResult<Address> {
+    let module: Module = hdk::utils::get_as_type(module_address.clone())?;
+    let course_address = module.course_address;
+    if !course::get_students(course_address.clone())?.contains(&student_address) {
+        return Err(ZomeApiError::from(String::from("Student is not enrolled in this course")));
+    }
+
+    let mut grade = GradeItem::new(course_address.clone(), module_address, student_address.clone(), category, score, max_score, 0);
+    if let Some(existing) = get_student_grade_items(&course_address, &student_address)?
+        .into_iter()
+        .find(|item| item.grades_same_work(&grade))
+    {
+        grade.revision = existing.revision + 1;
+    }
+    hdk::commit_entry(&grade.entry())
+}
+
+/// Without a grading scheme every category that has grades counts the same.
+fn grade_categories(scheme: &Option<GradingScheme>, items: &[GradeItem]) -> Vec<GradeCategory> {
+    match scheme {
+        Some(scheme) => scheme.categories.clone(),
+        None => {
+            let mut categories: Vec<GradeCategory> = Vec::new();
+            for item in items {
+                if !categories.iter().any(|category| category.name == item.category) {
+                    categories.push(GradeCategory {
+                        name: item.category.clone(),
+                        weight: 1,
+                    });
+                }
+            }
+            categories
+        }
+    }
+}
+
+fn student_grades(student_address: Address, items: Vec<GradeItem>, categories: &[GradeCategory]) -> StudentGrades {
+    let mut scores = Vec::new();
+    let mut weighted = 0.0;
+    let mut weights = 0.0;
+
+    for category in categories {
+        let graded: Vec<&GradeItem> = items.iter().filter(|item| item.category == category.name).collect();
+        if graded.is_empty() {
+            continue;
+        }
+        // summed wider than a single score, so many grades cannot overflow
+        let score: u64 = graded.iter().map(|item| u64::from(item.score)).sum();
+        let max_score: u64 = graded.iter().map(|item| u64::from(item.max_score)).sum();
+        let percentage = 100.0 * score as f64 / max_score as f64;
+
+        weighted += percentage * f64::from(category.weight);
+        weights += f64::from(category.weight);
+        scores.push(CategoryScore {
+            category: category.name.clone(),
+            percentage,
+        });
+    }
+
+    StudentGrades {
+        student_address,
+        items,
+        categories: scores,
+        total: if weights > 0.0 { Some(weighted / weights) } else { None },
+    }
+}
+
+pub fn get_gradebook(course_address: Address) -> ZomeApiResult<Gradebook> {
+    if !is_teacher(&course_address)? {
+        return Err(ZomeApiError::from(String::from(
+            "Only the teacher can see the gradebook",
+        )));
+    }
+    let scheme = get_scheme(&course_address)?;
+
+    let grade_items = get_grade_items(&course_address)?;
+    let mut all_items = Vec::new();
+    for student_address in course::get_students(course_address.clone())? {
+        let items: Vec<GradeItem> = grade_items
+            .iter()
+            .filter(|item| item.student_address == student_address)
+            .cloned()
+            .collect();
+        all_items.push((student_address, items));
+    }
+
+    let flat: Vec<GradeItem> = all_items.iter().flat_map(|(_, items)| items.clone()).collect();
+    let categories = grade_categories(&scheme, &flat);
+    let students = all_items
+        .into_iter()
+        .map(|(student_address, items)| student_grades(student_address, items, &categories))
+        .collect();
+
+    Ok(Gradebook {
+        course_address,
+        categories,
+        students,
+    })
+}
+
+pub fn get_my_grades(course_address: Address) -> ZomeApiResult<StudentGrades> {
+    let student_address: Address = AGENT_ADDRESS.to_string().into();
+    let course: Course = hdk::utils::get_as_type(course_address.clone())?;
+    let items: Vec<GradeItem> = message::send(
+        course.teacher_address,
+        DirectMessage::GradesRequest {
+            course_address: course_address.clone(),
+        },
+    )?;
+    let categories = grade_categories(&get_scheme(&course_address)?, &items);
+    Ok(student_grades(student_address, items, &categories))
+}
+
+/// Runs on the teacher's node: a student only ever gets their own grades.
+pub fn handle_grades_request(student_address: &Address, course_address: Address) -> ZomeApiResult<Vec<GradeItem>> {
+    if !is_teacher(&course_address)? {
+        return Err(ZomeApiError::from(String::from(
+            "Grades are kept by the teacher of the course",
+        )));
+    }
+    get_student_grade_items(&course_address, student_address)
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains(',') || value.contains('"') || value.contains('\n') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// One row per student with their percentage in each category and the
+/// weighted total; empty cells where there are no grades yet.
+pub fn export_gradebook_csv(course_address: Address) -> ZomeApiResult<String> {
+    let gradebook = get_gradebook(course_address)?;
+
+    let mut header = vec![String::from("student")];
+    header.extend(gradebook.categories.iter().map(|category| csv_field(&category.name)));
+    header.push(String::from("total"));
+
+    let mut rows = vec![header.join(",")];
+    for student in &gradebook.students {
+        let mut row = vec![student.student_address.to_string()];
+        for category in &gradebook.categories {
+            let cell = student
+                .categories
+                .iter()
+                .find(|score| score.category == category.name)
+                .map(|score| format!("{:.2}", score.percentage))
+                .unwrap_or_default();
+            row.push(cell);
+        }
+        row.push(student.total.map(|total| format!("{:.2}", total)).unwrap_or_default());
+        rows.push(row.join(","));
+    }
+    Ok(rows.join("\n"))
+}
@@ -0,0 +1,404 @@
+/************************ Import Required Libraries */
+use hdk::prelude::*;
+
+use crate::cohort;
+use crate::course;
+use crate::course::Course;
+use crate::encryption;
+use crate::enrollment::EnrollmentPolicy;
+use crate::message;
+use crate::message::DirectMessage;
+use crate::prerequisite;
+use crate::purchase;
+use crate::waitlist;
+use hdk::holochain_core_types::entry::cap_entries::{CapFunctions, CapabilityType};
+use hdk::AGENT_ADDRESS;
+use holochain_wasm_utils::api_serialization::QueryArgsNames;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+/******************************************* */
+
+/// Kept on the teacher's chain only: the address of this entry is the code
+/// handed out to students, and the signed salt keeps it unguessable.
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct InviteCode {
+    pub course_address: Address,
+    pub max_uses: u32,
+    salt: String,
+}
+
+impl InviteCode {
+    pub fn new(course_address: Address, max_uses: u32, salt: String) -> Self {
+        InviteCode {
+            course_address,
+            max_uses,
+            salt,
+        }
+    }
+
+    pub fn entry(&self) -> Entry {
+        Entry::App("invite_code".into(), self.into())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct InviteRedemption {
+    pub invite_address: Address,
+    pub student_address: Address,
+    pub grant_address: Address,
+}
+
+impl InviteRedemption {
+    pub fn new(invite_address: Address, student_address: Address, grant_address: Address) -> Self {
+        InviteRedemption {
+            invite_address,
+            student_address,
+            grant_address,
+        }
+    }
+
+    pub fn entry(&self) -> Entry {
+        Entry::App("invite_redemption".into(), self.into())
+    }
+}
+
+/// Revokes either an invite code or a capability grant issued for a course.
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct AccessRevocation {
+    pub revoked_address: Address,
+}
+
+impl AccessRevocation {
+    pub fn new(revoked_address: Address) -> Self {
+        AccessRevocation { revoked_address }
+    }
+
+    pub fn entry(&self) -> Entry {
+        Entry::App("access_revocation".into(), self.into())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct InviteCodeStatus {
+    pub code: Address,
+    pub max_uses: u32,
+    pub uses: u32,
+    pub revoked: bool,
+}
+
+////////////////////Invite Entry Definitions
+pub fn invite_code_entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "invite_code",
+        description: "an invite code the teacher hands out for a private course",
+        sharing: Sharing::Private,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: | validation_data: hdk::EntryValidationData<InviteCode> | {
+            match validation_data {
+                EntryValidationData::Create {entry, validation_data} => {
+                    validate_teacher(&validation_data.sources(), &entry.course_address)?;
+                    if entry.max_uses == 0 {
+                        return Err(String::from("An invite code needs at least one use"));
+                    }
+                    Ok(())
+                },
+                _ => Err(String::from("Invite codes are revoked, not modified or deleted"))
+            }
+        }
+    )
+}
+
+pub fn invite_redemption_entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "invite_redemption",
+        description: "a record of a student redeeming an invite code",
+        sharing: Sharing::Private,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: | _validation_data: hdk::EntryValidationData<InviteRedemption> | {
+            Ok(())
+        }
+    )
+}
+
+pub fn access_revocation_entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "access_revocation",
+        description: "a revoked invite code or course capability grant",
+        sharing: Sharing::Private,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: | _validation_data: hdk::EntryValidationData<AccessRevocation> | {
+            Ok(())
+        }
+    )
+}
+
+/*********************** Invite Validations */
+fn validate_teacher(signing_addresses: &Vec<Address>, course_address: &Address) -> ZomeApiResult<()> {
+    let course: Course = hdk::utils::get_as_type(course_address.clone())?;
+    if !signing_addresses.contains(&course.teacher_address) {
+        return Err(ZomeApiError::from(String::from(
+            "Only the teacher can manage invites for their course",
+        )));
+    }
+    Ok(())
+}
+
+fn validate_is_teacher(course_address: &Address) -> ZomeApiResult<Course> {
+    let course: Course = hdk::utils::get_as_type(course_address.clone())?;
+    if course.teacher_address != Address::from(AGENT_ADDRESS.to_string()) {
+        return Err(ZomeApiError::from(String::from(
+            "Only the teacher can manage invites for their course",
+        )));
+    }
+    Ok(course)
+}
+
+/// Helper Functions
+pub fn access_id(course_address: &Address) -> String {
+    format!("course:{}", course_address)
+}
+
+fn query_entries(entry_type: &str) -> ZomeApiResult<Vec<(Address, Entry)>> {
+    let result = hdk::query_result(
+        QueryArgsNames::QueryName(entry_type.into()),
+        QueryArgsOptions {
+            start: 0,
+            limit: 0,
+            headers: false,
+            entries: true,
+        },
+    )?;
+
+    match result {
+        QueryResult::Entries(entries) => Ok(entries),
+        _ => Err(ZomeApiError::from(String::from("Unexpected query result"))),
+    }
+}
+
+pub(crate) fn query_app_entries<T: TryFrom<JsonString>>(entry_type: &str) -> ZomeApiResult<Vec<(Address, T)>> {
+    Ok(query_entries(entry_type)?
+        .into_iter()
+        .filter_map(|(address, entry)| match entry {
+            Entry::App(_, value) => T::try_from(value).ok().map(|value| (address, value)),
+            _ => None,
+        })
+        .collect())
+}
+
+fn get_invite(code: &Address) -> ZomeApiResult<InviteCode> {
+    query_app_entries::<InviteCode>("invite_code")?
+        .into_iter()
+        .find(|(address, _)| address == code)
+        .map(|(_, invite)| invite)
+        .ok_or_else(|| ZomeApiError::from(String::from("Invalid invite code")))
+}
+
+fn is_revoked(address: &Address) -> ZomeApiResult<bool> {
+    Ok(query_app_entries::<AccessRevocation>("access_revocation")?
+        .iter()
+        .any(|(_, revocation)| &revocation.revoked_address == address))
+}
+
+fn get_redemptions(invite_address: &Address) -> ZomeApiResult<Vec<InviteRedemption>> {
+    Ok(query_app_entries::<InviteRedemption>("invite_redemption")?
+        .into_iter()
+        .map(|(_, redemption)| redemption)
+        .filter(|redemption| &redemption.invite_address == invite_address)
+        .collect())
+}
+
+/// Making a course private also encrypts its contents, so the DHT only ever
+/// holds them sealed, and makes it invite only. Both stay as they are if the
+/// course is made public again.
+pub fn update_visibility(course_address: Address, private: bool) -> ZomeApiResult<Address> {
+    encryption::update_sealed(&course_address, |course| {
+        course.private = private;
+        if private {
+            course.enrollment_policy = EnrollmentPolicy::InviteOnly;
+            if course.content_key_version.is_none() {
+                course.content_key_version = Some(1);
+            }
+        }
+    })
+}
+
+pub fn create_invite_code(course_address: Address, max_uses: u32) -> ZomeApiResult<Address> {
+    validate_is_teacher(&course_address)?;
+    let issued = query_entries("invite_code")?.len();
+    let salt = hdk::sign(format!("{}:{}", access_id(&course_address), issued))?;
+    hdk::commit_entry(&InviteCode::new(course_address, max_uses, salt).entry())
+}
+
+pub fn get_invite_codes(course_address: Address) -> ZomeApiResult<Vec<InviteCodeStatus>> {
+    validate_is_teacher(&course_address)?;
+    query_app_entries::<InviteCode>("invite_code")?
+        .into_iter()
+        .filter(|(_, invite)| invite.course_address == course_address)
+        .map(|(code, invite)| {
+            Ok(InviteCodeStatus {
+                uses: get_redemptions(&code)?.len() as u32,
+                revoked: is_revoked(&code)?,
+                max_uses: invite.max_uses,
+                code,
+            })
+        })
+        .collect()
+}
+
+pub fn revoke_invite_code(code: Address) -> ZomeApiResult<Address> {
+    let invite = get_invite(&code)?;
+    validate_is_teacher(&invite.course_address)?;
+    hdk::commit_entry(&AccessRevocation::new(code).entry())
+}
+
+/// The grants handed to `student_address` for the course, as recorded when
+/// they redeemed an invite.
+fn get_student_grants(course_address: &Address, student_address: &Address) -> ZomeApiResult<Vec<Address>> {
+    let mut grants = Vec::new();
+    for (_, redemption) in query_app_entries::<InviteRedemption>("invite_redemption")? {
+        if &redemption.student_address == student_address
+            && &get_invite(&redemption.invite_address)?.course_address == course_address
+        {
+            grants.push(redemption.grant_address);
+        }
+    }
+    Ok(grants)
+}
+
+/// Revokes every grant the student holds for the course and drops their enrollment.
+pub fn revoke_course_access(course_address: Address, student_address: Address) -> ZomeApiResult<Vec<Address>> {
+    validate_is_teacher(&course_address)?;
+    let mut revoked = Vec::new();
+    for grant_address in get_student_grants(&course_address, &student_address)? {
+        if !is_revoked(&grant_address)? {
+            hdk::commit_entry(&AccessRevocation::new(grant_address.clone()).entry())?;
+            revoked.push(grant_address);
+        }
+    }
+    course::remove_student(course_address, student_address)?;
+    Ok(revoked)
+}
+
+/// Sends the code to the teacher's node and keeps the returned grant as a claim.
+/// An invite to a priced course is redeemed with a payment, which a later
+/// attempt reuses if the teacher turns the code down.
+pub fn redeem(course_address: Address, code: Address, cohort_address: Option<Address>) -> ZomeApiResult<Address> {
+    let course: Course = hdk::utils::get_as_type(course_address.clone())?;
+    let payment_address = match course.price {
+        Some(_) => {
+            prerequisite::validate_student_prerequisites(&course, &AGENT_ADDRESS)?;
+            Some(purchase::find_or_pay(&course_address, &course)?)
+        }
+        None => None,
+    };
+    let token: Address = message::send(
+        course.teacher_address.clone(),
+        DirectMessage::RedeemInvite {
+            course_address: course_address.clone(),
+            code,
+            cohort_address,
+            payment_address,
+        },
+    )?;
+    hdk::commit_capability_claim(access_id(&course_address), course.teacher_address, token)
+}
+
+/// Runs on the teacher's node when a student sends an invite code.
+/// Returns the capability token granted to the student. The grant is
+/// transferable so the teacher's node can present it on the student's behalf
+/// (see `content::handle_content_request`); it is tied to the student by the
+/// redemption record instead.
+pub fn handle_redeem(
+    student_address: &Address,
+    course_address: Address,
+    code: Address,
+    cohort_address: Option<Address>,
+    payment_address: Option<Address>,
+) -> ZomeApiResult<Address> {
+    let course = validate_is_teacher(&course_address)?;
+
+    let invite = get_invite(&code)?;
+    if invite.course_address != course_address {
+        return Err(ZomeApiError::from(String::from("Invalid invite code")));
+    }
+    if is_revoked(&code)? {
+        return Err(ZomeApiError::from(String::from("Invite code has been revoked")));
+    }
+
+    let redemptions = get_redemptions(&code)?;
+    if let Some(redemption) = redemptions.iter().find(|r| &r.student_address == student_address) {
+        if is_revoked(&redemption.grant_address)? {
+            return Err(ZomeApiError::from(String::from(
+                "Your access to this course has been revoked",
+            )));
+        }
+        return Ok(redemption.grant_address.clone());
+    }
+    if redemptions.len() >= invite.max_uses as usize {
+        return Err(ZomeApiError::from(String::from("Invite code has no uses left")));
+    }
+    if waitlist::is_full(&course, &course_address)? {
+        return Err(ZomeApiError::from(String::from("Course is full")));
+    }
+    cohort::check_selection(&course_address, &cohort_address)?;
+    prerequisite::validate_student_prerequisites(&course, student_address)?;
+    let tag = match (course.price, payment_address) {
+        (None, _) => String::new(),
+        (Some(_), Some(payment_address)) => payment_address.to_string(),
+        (Some(_), None) => {
+            return Err(ZomeApiError::from(String::from(
+                "Enrollment in a priced course needs a payment",
+            )))
+        }
+    };
+    purchase::validate_payment(&course_address, student_address, &tag)?;
+
+    hdk::link_entries(student_address, &course_address, "student->courses", "")?;
+    hdk::link_entries(&course_address, student_address, "course->students", tag.as_str())?;
+    cohort::join(&cohort_address, student_address)?;
+
+    let mut functions: CapFunctions = BTreeMap::new();
+    functions.insert("courses".into(), vec!["serve_content".into()]);
+    let grant_address = hdk::commit_capability_grant(
+        access_id(&course_address),
+        CapabilityType::Transferable,
+        None,
+        functions,
+    )?;
+
+    hdk::commit_entry(&InviteRedemption::new(code, student_address.clone(), grant_address.clone()).entry())?;
+    Ok(grant_address)
+}
+
+/// Runs on the teacher's node: checks that `token` is a live grant for the
+/// course, handed to the agent asking for it.
+pub fn validate_access_token(student_address: &Address, course_address: &Address, token: &Address) -> ZomeApiResult<()> {
+    let granted = get_student_grants(course_address, student_address)?.contains(token);
+    if !granted || is_revoked(token)? {
+        return Err(ZomeApiError::from(String::from(
+            "No valid access grant for this course",
+        )));
+    }
+    Ok(())
+}
+
+pub fn get_my_access_token(course_address: &Address) -> ZomeApiResult<Address> {
+    query_entries("%cap_token_claim")?
+        .into_iter()
+        .filter_map(|(_, entry)| match entry {
+            Entry::CapTokenClaim(claim) => Some(claim),
+            _ => None,
+        })
+        .filter(|claim| claim.id() == access_id(course_address))
+        .last()
+        .map(|claim| claim.token())
+        .ok_or_else(|| ZomeApiError::from(String::from(
+            "You do not have access to this course",
+        )))
+}
@@ -13,6 +13,10 @@ extern crate serde_derive;
 extern crate serde_json;
 #[macro_use]
 extern crate holochain_json_derive;
+extern crate base64;
+extern crate chacha20poly1305;
+extern crate chrono;
+extern crate sha2;
 
 use hdk::prelude::*;
 
@@ -27,9 +31,30 @@ use hdk_proc_macros::zome;
 
 /******************************** */
 
+mod access;
+mod analytics;
+mod bundle;
+mod cartridge;
+mod clock;
+mod cohort;
 mod content;
 mod course;
+mod dashboard;
+mod encryption;
+mod enrollment;
+mod fork;
+mod gradebook;
+mod invite;
+mod message;
+mod moderation;
 mod module;
+mod path;
+mod prerequisite;
+mod purchase;
+mod schedule;
+mod teacher;
+mod transfer;
+mod waitlist;
 use course::Course;
 
 
@@ -46,11 +71,22 @@ mod my_zome {
         Ok(())
     }
 
+    #[receive]
+    pub fn receive(from: Address, msg_json: String) -> String {
+        message::receive(from, msg_json)
+    }
+
     #[zome_fn("hc_public")]
     fn get_my_address() -> ZomeApiResult<Address> {
         Ok(AGENT_ADDRESS.to_string().into())
     }
 
+    /***** Clock entry definition */
+    #[entry_def]
+    fn clock_tick_entry_definition() -> ValidatingEntryType {
+        clock::entry_def()
+    }
+
     /************ Course entry definition and function */
     #[entry_def]
     fn anchor_entry_definition() -> ValidatingEntryType {
@@ -64,12 +100,17 @@ mod my_zome {
 
     #[zome_fn("hc_public")]
     fn get_entry(address: Address) -> ZomeApiResult<Option<Entry>> {
-        hdk::get_entry(&address)
+        access::get_entry(address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_course_flag(course_address: Address) -> ZomeApiResult<Option<String>> {
+        course::get_flag(course_address)
     }
 
     #[zome_fn("hc_public")]
-    fn create_course(title: String, timestamp: u64) -> ZomeApiResult<Address> {
-        course::create(title, timestamp)
+    fn create_course(title: String) -> ZomeApiResult<Address> {
+        course::create(title)
     }
 
     #[zome_fn("hc_public")]
@@ -92,19 +133,419 @@ mod my_zome {
         course::get_my_courses()
     }
 
+    #[zome_fn("hc_public")]
+    fn get_teacher_courses(teacher_address: Address) -> ZomeApiResult<Vec<Address>> {
+        course::get_teacher_courses(teacher_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_teacher_summary(teacher_address: Address) -> ZomeApiResult<teacher::TeacherSummary> {
+        teacher::get_teacher_summary(teacher_address)
+    }
+
     #[zome_fn("hc_public")]
     fn get_my_enrolled_courses() -> ZomeApiResult<Vec<Address>> {
         course::get_my_enrolled_courses()
     }
 
     #[zome_fn("hc_public")]
-    fn enrol_in_course(course_address: Address) -> ZomeApiResult<Address> {
-        course::enrol_in_course(course_address)
+    fn enrol_in_course(course_address: Address, cohort_address: Option<Address>) -> ZomeApiResult<Address> {
+        course::enrol_in_course(course_address, cohort_address)
     }
 
     #[zome_fn("hc_public")]
-    fn get_students(course_address: Address) -> ZomeApiResult<Vec<Address>> {
-        course::get_students(course_address)
+    fn get_students(course_address: Address, cohort_address: Option<Address>) -> ZomeApiResult<Vec<Address>> {
+        match cohort_address {
+            Some(cohort_address) => cohort::get_course_students(course_address, cohort_address),
+            None => course::get_students(course_address),
+        }
+    }
+
+    #[zome_fn("hc_public")]
+    fn unenrol_from_course(course_address: Address) -> ZomeApiResult<Option<Address>> {
+        course::unenrol_from_course(course_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn remove_student(course_address: Address, student_address: Address) -> ZomeApiResult<Option<Address>> {
+        course::remove_student(course_address, student_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn update_course_capacity(course_address: Address, max_students: Option<u32>) -> ZomeApiResult<Address> {
+        course::update_capacity(course_address, max_students)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_waitlist(course_address: Address) -> ZomeApiResult<Vec<Address>> {
+        waitlist::get_waitlist(course_address)
+    }
+
+    /***** Enrollment request entry definition and functions */
+    #[entry_def]
+    fn enrollment_request_entry_definition() -> ValidatingEntryType {
+        enrollment::entry_def()
+    }
+
+    #[zome_fn("hc_public")]
+    fn update_course_enrollment_policy(course_address: Address, enrollment_policy: enrollment::EnrollmentPolicy) -> ZomeApiResult<Address> {
+        enrollment::update_policy(course_address, enrollment_policy)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_enrollment_requests(course_address: Address) -> ZomeApiResult<Vec<Address>> {
+        enrollment::get_enrollment_requests(course_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn approve_enrollment(request_address: Address) -> ZomeApiResult<Address> {
+        enrollment::approve(request_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn reject_enrollment(request_address: Address) -> ZomeApiResult<Address> {
+        enrollment::reject(request_address)
+    }
+
+    /***** Encrypted course contents */
+    #[entry_def]
+    fn course_key_entry_definition() -> ValidatingEntryType {
+        encryption::entry_def()
+    }
+
+    #[zome_fn("hc_public")]
+    fn update_course_encryption(course_address: Address, encrypted: bool) -> ZomeApiResult<Address> {
+        encryption::update_encryption(course_address, encrypted)
+    }
+
+    #[zome_fn("hc_public")]
+    fn rotate_course_key(course_address: Address) -> ZomeApiResult<Address> {
+        encryption::rotate_key(course_address)
+    }
+
+    /***** Invite codes and private course access */
+    #[entry_def]
+    fn invite_code_entry_definition() -> ValidatingEntryType {
+        invite::invite_code_entry_def()
+    }
+
+    #[entry_def]
+    fn invite_redemption_entry_definition() -> ValidatingEntryType {
+        invite::invite_redemption_entry_def()
+    }
+
+    #[entry_def]
+    fn access_revocation_entry_definition() -> ValidatingEntryType {
+        invite::access_revocation_entry_def()
+    }
+
+    #[zome_fn("hc_public")]
+    fn update_course_visibility(course_address: Address, private: bool) -> ZomeApiResult<Address> {
+        invite::update_visibility(course_address, private)
+    }
+
+    #[zome_fn("hc_public")]
+    fn create_invite_code(course_address: Address, max_uses: u32) -> ZomeApiResult<Address> {
+        invite::create_invite_code(course_address, max_uses)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_invite_codes(course_address: Address) -> ZomeApiResult<Vec<invite::InviteCodeStatus>> {
+        invite::get_invite_codes(course_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn revoke_invite_code(code: Address) -> ZomeApiResult<Address> {
+        invite::revoke_invite_code(code)
+    }
+
+    #[zome_fn("hc_public")]
+    fn redeem_invite_code(course_address: Address, code: Address, cohort_address: Option<Address>) -> ZomeApiResult<Address> {
+        invite::redeem(course_address, code, cohort_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn revoke_course_access(course_address: Address, student_address: Address) -> ZomeApiResult<Vec<Address>> {
+        invite::revoke_course_access(course_address, student_address)
+    }
+
+    /***** Prerequisites and completion entry definition and functions */
+    #[entry_def]
+    fn completion_entry_definition() -> ValidatingEntryType {
+        prerequisite::entry_def()
+    }
+
+    #[zome_fn("hc_public")]
+    fn update_course_prerequisites(course_address: Address, prerequisites: Vec<Address>) -> ZomeApiResult<Address> {
+        prerequisite::update_prerequisites(course_address, prerequisites)
+    }
+
+    #[zome_fn("hc_public")]
+    fn issue_completion(course_address: Address, student_address: Address) -> ZomeApiResult<Address> {
+        prerequisite::issue_completion(course_address, student_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn revoke_completion(completion_address: Address) -> ZomeApiResult<Address> {
+        prerequisite::revoke_completion(completion_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_my_completions() -> ZomeApiResult<Vec<Address>> {
+        prerequisite::get_my_completions()
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_prerequisite_graph(course_address: Address) -> ZomeApiResult<prerequisite::PrerequisiteGraph> {
+        prerequisite::get_prerequisite_graph(course_address)
+    }
+
+    /***** Gradebook entry definitions and functions */
+    #[entry_def]
+    fn grading_scheme_entry_definition() -> ValidatingEntryType {
+        gradebook::grading_scheme_entry_def()
+    }
+
+    #[entry_def]
+    fn grade_item_entry_definition() -> ValidatingEntryType {
+        gradebook::grade_item_entry_def()
+    }
+
+    #[zome_fn("hc_public")]
+    fn update_grading_scheme(course_address: Address, categories: Vec<gradebook::GradeCategory>) -> ZomeApiResult<Address> {
+        gradebook::update_grading_scheme(course_address, categories)
+    }
+
+    #[zome_fn("hc_public")]
+    fn grade_student(
+        module_address: Address,
+        student_address: Address,
+        category: String,
+        score: u32,
+        max_score: u32,
+    ) -> ZomeApiResult<Address> {
+        gradebook::grade_student(module_address, student_address, category, score, max_score)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_gradebook(course_address: Address) -> ZomeApiResult<gradebook::Gradebook> {
+        gradebook::get_gradebook(course_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_my_grades(course_address: Address) -> ZomeApiResult<gradebook::StudentGrades> {
+        gradebook::get_my_grades(course_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn export_gradebook_csv(course_address: Address) -> ZomeApiResult<String> {
+        gradebook::export_gradebook_csv(course_address)
+    }
+
+    /***** Course analytics */
+    #[entry_def]
+    fn content_view_entry_definition() -> ValidatingEntryType {
+        analytics::entry_def()
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_course_analytics(course_address: Address) -> ZomeApiResult<analytics::CourseAnalytics> {
+        analytics::get_course_analytics(course_address)
+    }
+
+    /***** Student dashboard */
+    #[entry_def]
+    fn last_seen_entry_definition() -> ValidatingEntryType {
+        dashboard::entry_def()
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_my_dashboard() -> ZomeApiResult<dashboard::Dashboard> {
+        dashboard::get_my_dashboard()
+    }
+
+    /***** Moderation entry definitions and functions */
+    #[entry_def]
+    fn report_entry_definition() -> ValidatingEntryType {
+        moderation::report_entry_def()
+    }
+
+    #[entry_def]
+    fn moderation_entry_definition() -> ValidatingEntryType {
+        moderation::moderation_entry_def()
+    }
+
+    #[entry_def]
+    fn appeal_entry_definition() -> ValidatingEntryType {
+        moderation::appeal_entry_def()
+    }
+
+    #[zome_fn("hc_public")]
+    fn report(address: Address, reason: String) -> ZomeApiResult<Address> {
+        moderation::report(address, reason)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_open_reports() -> ZomeApiResult<Vec<moderation::OpenItem>> {
+        moderation::get_open_reports()
+    }
+
+    #[zome_fn("hc_public")]
+    fn moderate(address: Address, action: moderation::ModerationAction, reason: String) -> ZomeApiResult<Address> {
+        moderation::moderate(address, action, reason)
+    }
+
+    #[zome_fn("hc_public")]
+    fn appeal(address: Address, reason: String) -> ZomeApiResult<Address> {
+        moderation::appeal(address, reason)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_moderation_trail(address: Address) -> ZomeApiResult<moderation::ModerationTrail> {
+        moderation::get_moderation_trail(address)
+    }
+
+    /***** Course export and import */
+    #[zome_fn("hc_public")]
+    fn export_course(course_address: Address) -> ZomeApiResult<bundle::CourseBundle> {
+        bundle::export_course(course_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn import_course(bundle: bundle::CourseBundle) -> ZomeApiResult<bundle::ImportReport> {
+        bundle::import_course(bundle)
+    }
+
+    #[zome_fn("hc_public")]
+    fn convert_cartridge(manifest: String) -> ZomeApiResult<cartridge::CartridgeConversion> {
+        cartridge::convert_cartridge(manifest)
+    }
+
+    /***** Course cloning */
+    #[zome_fn("hc_public")]
+    fn clone_course(source_address: Address, new_title: String) -> ZomeApiResult<bundle::ImportReport> {
+        fork::clone_course(source_address, new_title)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_forked_from(course_address: Address) -> ZomeApiResult<Option<Address>> {
+        fork::get_forked_from(course_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn update_course_forking(course_address: Address, forkable: bool) -> ZomeApiResult<Address> {
+        fork::update_forking(course_address, forkable)
+    }
+
+    /***** Learning path entry definition and functions */
+    #[entry_def]
+    fn learning_path_entry_definition() -> ValidatingEntryType {
+        path::entry_def()
+    }
+
+    #[zome_fn("hc_public")]
+    fn create_path(title: String, courses: Vec<Address>) -> ZomeApiResult<Address> {
+        path::create_path(title, courses)
+    }
+
+    #[zome_fn("hc_public")]
+    fn update_path(path_address: Address, title: String, courses: Vec<Address>) -> ZomeApiResult<Address> {
+        path::update_path(path_address, title, courses)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_path(path_address: Address) -> ZomeApiResult<path::PathDetails> {
+        path::get_path(path_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn list_paths() -> ZomeApiResult<Vec<Address>> {
+        path::list_paths()
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_my_paths() -> ZomeApiResult<Vec<Address>> {
+        path::get_my_paths()
+    }
+
+    #[zome_fn("hc_public")]
+    fn enrol_in_path(path_address: Address, cohorts: Vec<path::PathCohort>) -> ZomeApiResult<path::PathEnrollment> {
+        path::enrol_in_path(path_address, cohorts)
+    }
+
+    /***** Cohort entry definition and functions */
+    #[entry_def]
+    fn cohort_entry_definition() -> ValidatingEntryType {
+        cohort::entry_def()
+    }
+
+    #[zome_fn("hc_public")]
+    fn create_cohort(
+        course_address: Address,
+        title: String,
+        start_date: u64,
+        end_date: u64,
+        max_students: Option<u32>,
+    ) -> ZomeApiResult<Address> {
+        cohort::create(course_address, title, start_date, end_date, max_students)
+    }
+
+    #[zome_fn("hc_public")]
+    fn update_cohort(
+        cohort_address: Address,
+        title: String,
+        start_date: u64,
+        end_date: u64,
+        max_students: Option<u32>,
+    ) -> ZomeApiResult<Address> {
+        cohort::update(cohort_address, title, start_date, end_date, max_students)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_cohorts(course_address: Address) -> ZomeApiResult<Vec<Address>> {
+        cohort::get_cohorts(course_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn move_student(cohort_address: Address, student_address: Address) -> ZomeApiResult<Address> {
+        cohort::move_student(cohort_address, student_address)
+    }
+
+    /***** Course pricing and purchases */
+    #[zome_fn("hc_public")]
+    fn update_course_price(course_address: Address, price: Option<u64>) -> ZomeApiResult<Address> {
+        purchase::update_price(course_address, price)
+    }
+
+    #[zome_fn("hc_public")]
+    fn purchase_course(course_address: Address, cohort_address: Option<Address>) -> ZomeApiResult<Address> {
+        purchase::purchase_course(course_address, cohort_address)
+    }
+
+    /***** Course transfer entry definition and functions */
+    #[entry_def]
+    fn course_transfer_entry_definition() -> ValidatingEntryType {
+        transfer::entry_def()
+    }
+
+    #[zome_fn("hc_public")]
+    fn propose_course_transfer(course_address: Address, new_teacher_address: Address) -> ZomeApiResult<Address> {
+        transfer::propose(course_address, new_teacher_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_my_transfer_proposals() -> ZomeApiResult<Vec<Address>> {
+        transfer::get_my_transfer_proposals()
+    }
+
+    #[zome_fn("hc_public")]
+    fn accept_course_transfer(transfer_address: Address) -> ZomeApiResult<Address> {
+        transfer::accept(transfer_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn cancel_course_transfer(transfer_address: Address) -> ZomeApiResult<Address> {
+        transfer::cancel(transfer_address)
     }
 
     
@@ -115,8 +556,8 @@ mod my_zome {
     }
 
     #[zome_fn("hc_public")]
-    fn create_module(title: String, course_address: Address, timestamp: u64) -> ZomeApiResult<Address> {
-        module::create(title, &course_address, timestamp)
+    fn create_module(title: String, course_address: Address) -> ZomeApiResult<Address> {
+        module::create(title, &course_address)
     }
 
     #[zome_fn("hc_public")]
@@ -129,6 +570,21 @@ mod my_zome {
         module::delete(module_address)
     }
 
+    #[zome_fn("hc_public")]
+    fn update_module_release(module_address: Address, release_offset: Option<u64>, release_at: Option<u64>) -> ZomeApiResult<Address> {
+        schedule::update_release(module_address, release_offset, release_at)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_module(module_address: Address) -> ZomeApiResult<module::Module> {
+        schedule::get_module(module_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_modules(course_address: Address) -> ZomeApiResult<Vec<Address>> {
+        schedule::get_modules(course_address)
+    }
+
 
     /***** Content entry definition and functions */
     #[entry_def]
@@ -136,14 +592,25 @@ mod my_zome {
         content::entry_def()
     }
 
+    #[zome_fn("hc_public")]
+    fn get_content(content_address: Address) -> ZomeApiResult<content::Content> {
+        content::get(content_address)
+    }
+
+    // Not public: the conductor only runs it under an access grant of a private course
+    #[zome_fn("course_access")]
+    fn serve_content(content_address: Address, student_address: Address, token: Address) -> ZomeApiResult<content::Content> {
+        content::serve(content_address, &student_address, &token)
+    }
+
     #[zome_fn("hc_public")] 
     fn get_contents(module_address: Address) -> ZomeApiResult<Vec<Address>> {
-        content::get_contents(&module_address)
+        content::get_released_contents(&module_address)
     }
 
     #[zome_fn("hc_public")]
-    fn create_content(name: String, module_address: Address, url: String, timestamp: u64, description: String) -> ZomeApiResult<Address> {
-        content::create(name, module_address, url, timestamp, description)
+    fn create_content(name: String, module_address: Address, url: String, description: String) -> ZomeApiResult<Address> {
+        content::create(name, module_address, url, description)
     }
 
     #[zome_fn("hc_public")]
@@ -0,0 +1,66 @@
+/************************ Import Required Libraries */
+use hdk::prelude::*;
+
+use crate::content;
+use crate::course;
+use crate::encryption;
+use crate::gradebook;
+use crate::invite;
+use hdk::holochain_core_types::time::Timeout;
+use serde::de::DeserializeOwned;
+use std::convert::TryFrom;
+/******************************************* */
+
+/// Messages exchanged directly between nodes with `hdk::send`.
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub enum DirectMessage {
+    WaitlistPromotion { course_address: Address, enrolled: bool },
+    StudentLeft { course_address: Address },
+    RedeemInvite { course_address: Address, code: Address, cohort_address: Option<Address>, payment_address: Option<Address> },
+    ContentRequest { content_address: Address, token: Address },
+    KeyRequest { course_address: Address, key_version: u32, token: Option<Address> },
+    CourseKey { course_address: Address, key_version: u32, key: String },
+    GradesRequest { course_address: Address },
+}
+
+/// Sends `message` and decodes the `Result` the other node answered with.
+pub fn send<R: DeserializeOwned>(to_agent: Address, message: DirectMessage) -> ZomeApiResult<R> {
+    let response = hdk::send(to_agent, JsonString::from(message).to_string(), Timeout::default())?;
+    let result: Result<R, String> = serde_json::from_str(&response)
+        .map_err(|_| ZomeApiError::from(format!("Unexpected response: {}", response)))?;
+    result.map_err(ZomeApiError::from)
+}
+
+pub fn receive(from: Address, payload: String) -> String {
+    let message: Result<DirectMessage, _> = serde_json::from_str(&payload);
+
+    let result = match message {
+        Ok(DirectMessage::WaitlistPromotion { course_address, enrolled }) => hdk::emit_signal(
+            "waitlist_promotion",
+            json!({ "course_address": course_address, "enrolled": enrolled, "from": from }),
+        )
+        .map(|_| json!(null)),
+        Ok(DirectMessage::StudentLeft { course_address }) => {
+            course::handle_student_left(course_address).map(|promoted| json!(promoted))
+        }
+        Ok(DirectMessage::RedeemInvite { course_address, code, cohort_address, payment_address }) => {
+            invite::handle_redeem(&from, course_address, code, cohort_address, payment_address).map(|token| json!(token))
+        }
+        Ok(DirectMessage::ContentRequest { content_address, token }) => {
+            content::handle_content_request(&from, content_address, token).map(|content| json!(content))
+        }
+        Ok(DirectMessage::KeyRequest { course_address, key_version, token }) => {
+            encryption::handle_key_request(&from, course_address, key_version, token).map(|key| json!(key))
+        }
+        Ok(DirectMessage::CourseKey { course_address, key_version, key }) => {
+            encryption::handle_course_key(&from, course_address, key_version, key).map(|address| json!(address))
+        }
+        Ok(DirectMessage::GradesRequest { course_address }) => {
+            gradebook::handle_grades_request(&from, course_address).map(|items| json!(items))
+        }
+        Err(_) => Err(ZomeApiError::from(String::from("Unknown message"))),
+    };
+
+    let response: Result<serde_json::Value, String> = result.map_err(|error| error.to_string());
+    json!(response).to_string()
+}
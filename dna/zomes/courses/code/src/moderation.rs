@@ -0,0 +1,506 @@
+/************************ Import Required Libraries */
+use hdk::prelude::*;
+
+use crate::clock;
+use crate::content::Content;
+use crate::course;
+use crate::course::Course;
+use crate::module::Module;
+use hdk::holochain_core_types::time::Iso8601;
+use hdk::AGENT_ADDRESS;
+use std::convert::TryFrom;
+/******************************************* */
+
+/// A member's complaint about a course, module or content.
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct Report {
+    pub item_address: Address,
+    pub reason: String,
+    pub reporter_address: Address,
+}
+
+impl Report {
+    pub fn new(item_address: Address, reason: String, reporter_address: Address) -> Self {
+        Report {
+            item_address,
+            reason,
+            reporter_address,
+        }
+    }
+
+    pub fn entry(&self) -> Entry {
+        Entry::App("report".into(), self.into())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ModerationAction {
+    Hide,    // Removes the item from public listings
+    Restore, // Lists a hidden item again
+    Dismiss, // Closes the reports without changing anything
+}
+
+/// A moderator's decision on an item. Signed by the moderator.
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct Moderation {
+    pub item_address: Address,
+    pub action: ModerationAction,
+    pub reason: String,
+    pub moderator_address: Address,
+    pub decided_at: Iso8601,
+}
+
+impl Moderation {
+    pub fn new(item_address: Address, action: ModerationAction, reason: String, moderator_address: Address, decided_at: Iso8601) -> Self {
+        Moderation {
+            item_address,
+            action,
+            reason,
+            moderator_address,
+            decided_at,
+        }
+    }
+
+    pub fn entry(&self) -> Entry {
+        Entry::App("moderation".into(), self.into())
+    }
+}
+
+/// The teacher's request to reconsider a decision to hide their item.
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct Appeal {
+    pub item_address: Address,
+    pub moderation_address: Address,
+    pub reason: String,
+    pub appellant_address: Address,
+}
+
+impl Appeal {
+    pub fn new(item_address: Address, moderation_address: Address, reason: String, appellant_address: Address) -> Self {
+        Appeal {
+            item_address,
+            moderation_address,
+            reason,
+            appellant_address,
+        }
+    }
+
+    pub fn entry(&self) -> Entry {
+        Entry::App("appeal".into(), self.into())
+    }
+}
+
+/// Everything waiting for a moderator about one item.
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct OpenItem {
+    pub item_address: Address,
+    pub reports: Vec<Report>,
+    pub appeals: Vec<Appeal>,
+}
+
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct ModerationTrail {
+    pub item_address: Address,
+    pub hidden: bool,
+    pub reports: Vec<Report>,
+    pub decisions: Vec<Moderation>, // Oldest first
+    pub appeals: Vec<Appeal>,
+}
+
+////////////////////Report Entry Definition
+pub fn report_entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "report",
+        description: "a member's report about a course, module or content",
+        sharing: Sharing::Public,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: | validation_data: hdk::EntryValidationData<Report> | {
+            match validation_data {
+                EntryValidationData::Create {entry, validation_data} => {
+                    if !validation_data.sources().contains(&entry.reporter_address) {
+                        return Err(String::from("A report must be signed by its reporter"));
+                    }
+                    validate_reason(&entry.reason)?;
+                    course::validate_member(&entry.reporter_address)?;
+                    item_teacher(&entry.item_address)?;
+                    Ok(())
+                },
+                _ => Err(String::from("Reports cannot be modified or deleted"))
+            }
+        },
+        links: [
+            from!( // from the reported item's moderation anchor
+                "anchor",
+                link_type: "reports",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    validate_item_link(validation_data, |report: Report| (report.item_address, report.reporter_address))
+                }
+            ),
+            from!(
+                "anchor",
+                link_type: "open_reports",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    validate_queue_link(validation_data)
+                }
+            )
+        ]
+    )
+}
+
+////////////////////Moderation Entry Definition
+pub fn moderation_entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "moderation",
+        description: "a moderator's decision to hide, restore or dismiss reports on an item",
+        sharing: Sharing::Public,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: | validation_data: hdk::EntryValidationData<Moderation> | {
+            match validation_data {
+                EntryValidationData::Create {entry, validation_data} => {
+                    if !validation_data.sources().contains(&entry.moderator_address) {
+                        return Err(String::from("A decision must be signed by its moderator"));
+                    }
+                    validate_moderator(&entry.moderator_address)?;
+                    validate_reason(&entry.reason)?;
+                    clock::validate_times(&validation_data.package.chain_header, &entry.decided_at, &entry.decided_at, None)?;
+                    Ok(())
+                },
+                _ => Err(String::from("Decisions cannot be modified or deleted, make a new one"))
+            }
+        },
+        links: [
+            from!( // from the item's moderation anchor
+                "anchor",
+                link_type: "moderations",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    if let hdk::LinkValidationData::LinkAdd { validation_data, .. } = &validation_data {
+                        if !validation_data.sources().iter().any(|source| validate_moderator(source).is_ok()) {
+                            return Err(String::from("Only moderators can moderate"));
+                        }
+                    }
+                    validate_item_link(validation_data, |moderation: Moderation| (moderation.item_address, moderation.moderator_address))
+                }
+            )
+        ]
+    )
+}
+
+////////////////////Appeal Entry Definition
+pub fn appeal_entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "appeal",
+        description: "a teacher's appeal against hiding their item",
+        sharing: Sharing::Public,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: | validation_data: hdk::EntryValidationData<Appeal> | {
+            match validation_data {
+                EntryValidationData::Create {entry, validation_data} => {
+                    if !validation_data.sources().contains(&entry.appellant_address) {
+                        return Err(String::from("An appeal must be signed by its appellant"));
+                    }
+                    if item_teacher(&entry.item_address)? != entry.appellant_address {
+                        return Err(String::from("Only the teacher of an item can appeal its moderation"));
+                    }
+                    let moderation: Moderation = hdk::utils::get_as_type(entry.moderation_address.clone())?;
+                    if moderation.item_address != entry.item_address || moderation.action != ModerationAction::Hide {
+                        return Err(String::from("Only a decision to hide this item can be appealed"));
+                    }
+                    validate_reason(&entry.reason)?;
+                    Ok(())
+                },
+                _ => Err(String::from("Appeals cannot be modified or deleted"))
+            }
+        },
+        links: [
+            from!( // from the item's moderation anchor
+                "anchor",
+                link_type: "appeals",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    validate_item_link(validation_data, |appeal: Appeal| (appeal.item_address, appeal.appellant_address))
+                }
+            ),
+            from!(
+                "anchor",
+                link_type: "open_appeals",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    validate_queue_link(validation_data)
+                }
+            )
+        ]
+    )
+}
+
+/*********************** Moderation Validations */
+fn validate_reason(reason: &str) -> ZomeApiResult<()> {
+    if reason.trim().is_empty() {
+        return Err(ZomeApiError::from(String::from("A reason is required")));
+    }
+    Ok(())
+}
+
+fn validate_moderator(agent_address: &Address) -> ZomeApiResult<()> {
+    if !course::has_role(agent_address, "Moderator")? {
+        return Err(ZomeApiError::from(String::from("Only moderators can moderate")));
+    }
+    Ok(())
+}
+
+/// Reports, decisions and appeals are found from the anchor of their item, so
+/// each must hang off the anchor of the item it is about and be linked by its
+/// author. `item_and_author` reads both from the linked entry.
+fn validate_item_link<T: TryFrom<JsonString>>(
+    validation_data: hdk::LinkValidationData,
+    item_and_author: fn(T) -> (Address, Address),
+) -> Result<(), String> {
+    match validation_data {
+        hdk::LinkValidationData::LinkAdd { link, validation_data } => {
+            let (item_address, author_address) = item_and_author(hdk::utils::get_as_type(link.link.target().clone())?);
+            if link.link.base() != &hdk::entry_address(&item_anchor_entry(&item_address))? {
+                return Err(String::from("A moderation record must be linked from the item it is about"));
+            }
+            if !validation_data.sources().contains(&author_address) {
+                return Err(String::from("A moderation record can only be linked by its author"));
+            }
+            Ok(())
+        },
+        hdk::LinkValidationData::LinkRemove { .. } => {
+            Err(String::from("Moderation records cannot be unlinked"))
+        }
+    }
+}
+
+/// Anyone can queue an item for review, only moderators can take it off the queue.
+fn validate_queue_link(validation_data: hdk::LinkValidationData) -> Result<(), String> {
+    match validation_data {
+        hdk::LinkValidationData::LinkAdd { .. } => Ok(()),
+        hdk::LinkValidationData::LinkRemove { validation_data, .. } => {
+            for source in validation_data.sources() {
+                if validate_moderator(&source).is_ok() {
+                    return Ok(());
+                }
+            }
+            Err(String::from("Only moderators can close reports"))
+        }
+    }
+}
+
+/// Helper Functions
+fn item_anchor_entry(item_address: &Address) -> Entry {
+    Entry::App("anchor".into(), format!("moderation:{}", item_address).into())
+}
+
+fn queue_anchor_entry() -> Entry {
+    Entry::App("anchor".into(), "moderation_queue".into())
+}
+
+/// The teacher responsible for a course, module or content.
+fn item_teacher(item_address: &Address) -> ZomeApiResult<Address> {
+    let entry_type = match hdk::get_entry(item_address)? {
+        Some(Entry::App(entry_type, _)) => entry_type.to_string(),
+        _ => String::new(),
+    };
+    let course_address = match entry_type.as_str() {
+        "course" => item_address.clone(),
+        "module" => hdk::utils::get_as_type::<Module>(item_address.clone())?.course_address,
+        "content" => {
+            let content: Content = hdk::utils::get_as_type(item_address.clone())?;
+            hdk::utils::get_as_type::<Module>(content.module_address().clone())?.course_address
+        }
+        _ => {
+            return Err(ZomeApiError::from(String::from(
+                "Only courses, modules and contents can be moderated",
+            )))
+        }
+    };
+    let course: Course = hdk::utils::get_as_type(course_address)?;
+    Ok(course.teacher_address)
+}
+
+fn get_decisions(item_address: &Address) -> ZomeApiResult<Vec<(Address, Moderation)>> {
+    let links = hdk::get_links(
+        &hdk::entry_address(&item_anchor_entry(item_address))?,
+        LinkMatch::Exactly("moderations"),
+        LinkMatch::Any,
+    )?;
+    let mut decisions = Vec::new();
+    for moderation_address in links.addresses() {
+        let moderation: Moderation = hdk::utils::get_as_type(moderation_address.clone())?;
+        if &moderation.item_address == item_address {
+            decisions.push((moderation_address, moderation));
+        }
+    }
+    decisions.sort_by(|(_, a), (_, b)| a.decided_at.cmp(&b.decided_at));
+    Ok(decisions)
+}
+
+/// The last decision to hide or restore the item wins; dismissals leave it as it was.
+pub fn is_hidden(item_address: &Address) -> ZomeApiResult<bool> {
+    Ok(get_decisions(item_address)?
+        .into_iter()
+        .rev()
+        .find(|(_, moderation)| moderation.action != ModerationAction::Dismiss)
+        .map(|(_, moderation)| moderation.action == ModerationAction::Hide)
+        .unwrap_or(false))
+}
+
+pub fn report(item_address: Address, reason: String) -> ZomeApiResult<Address> {
+    let item_anchor_address = hdk::commit_entry(&item_anchor_entry(&item_address))?;
+    let queue_anchor_address = hdk::commit_entry(&queue_anchor_entry())?;
+
+    let report = Report::new(item_address, reason, AGENT_ADDRESS.to_string().into());
+    let report_address = hdk::commit_entry(&report.entry())?;
+    hdk::link_entries(&item_anchor_address, &report_address, "reports", "")?;
+    hdk::link_entries(&queue_anchor_address, &report_address, "open_reports", "")?;
+    Ok(report_address)
+}
+
+/// Records the decision and closes the open reports and appeals on the item.
+pub fn moderate(item_address: Address, action: ModerationAction, reason: String) -> ZomeApiResult<Address> {
+    let item_anchor_address = hdk::commit_entry(&item_anchor_entry(&item_address))?;
+    let queue_anchor_address = hdk::commit_entry(&queue_anchor_entry())?;
+
+    let moderation = Moderation::new(item_address.clone(), action, reason, AGENT_ADDRESS.to_string().into(), clock::commit_time()?);
+    let moderation_address = hdk::commit_entry(&moderation.entry())?;
+    hdk::link_entries(&item_anchor_address, &moderation_address, "moderations", "")?;
+
+    for (report_address, report) in get_queued::<Report>("open_reports")? {
+        if report.item_address == item_address {
+            hdk::remove_link(&queue_anchor_address, &report_address, "open_reports", "")?;
+        }
+    }
+    for (appeal_address, appeal) in get_queued::<Appeal>("open_appeals")? {
+        if appeal.item_address == item_address {
+            hdk::remove_link(&queue_anchor_address, &appeal_address, "open_appeals", "")?;
+        }
+    }
+    Ok(moderation_address)
+}
+
+/// Appeals the latest decision to hide the item, putting it back in front of moderators.
+pub fn appeal(item_address: Address, reason: String) -> ZomeApiResult<Address> {
+    if !is_hidden(&item_address)? {
+        return Err(ZomeApiError::from(String::from("This item is not hidden")));
+    }
+    let (moderation_address, _) = get_decisions(&item_address)?
+        .into_iter()
+        .rev()
+        .find(|(_, moderation)| moderation.action == ModerationAction::Hide)
+        .ok_or_else(|| ZomeApiError::from(String::from("This item is not hidden")))?;
+
+    let item_anchor_address = hdk::commit_entry(&item_anchor_entry(&item_address))?;
+    let queue_anchor_address = hdk::commit_entry(&queue_anchor_entry())?;
+
+    let appeal = Appeal::new(item_address, moderation_address, reason, AGENT_ADDRESS.to_string().into());
+    let appeal_address = hdk::commit_entry(&appeal.entry())?;
+    hdk::link_entries(&item_anchor_address, &appeal_address, "appeals", "")?;
+    hdk::link_entries(&queue_anchor_address, &appeal_address, "open_appeals", "")?;
+    Ok(appeal_address)
+}
+
+fn get_queued<T: TryFrom<JsonString>>(link_type: &str) -> ZomeApiResult<Vec<(Address, T)>> {
+    let links = hdk::get_links(
+        &hdk::entry_address(&queue_anchor_entry())?,
+        LinkMatch::Exactly(link_type),
+        LinkMatch::Any,
+    )?;
+    let mut queued = Vec::new();
+    for address in links.addresses() {
+        queued.push((address.clone(), hdk::utils::get_as_type(address)?));
+    }
+    Ok(queued)
+}
+
+pub fn get_open_reports() -> ZomeApiResult<Vec<OpenItem>> {
+    validate_moderator(&AGENT_ADDRESS)?;
+
+    let mut open: Vec<OpenItem> = Vec::new();
+    for (_, report) in get_queued::<Report>("open_reports")? {
+        match open.iter_mut().find(|item| item.item_address == report.item_address) {
+            Some(item) => item.reports.push(report),
+            None => open.push(OpenItem {
+                item_address: report.item_address.clone(),
+                reports: vec![report],
+                appeals: Vec::new(),
+            }),
+        }
+    }
+    for (_, appeal) in get_queued::<Appeal>("open_appeals")? {
+        match open.iter_mut().find(|item| item.item_address == appeal.item_address) {
+            Some(item) => item.appeals.push(appeal),
+            None => open.push(OpenItem {
+                item_address: appeal.item_address.clone(),
+                reports: Vec::new(),
+                appeals: vec![appeal],
+            }),
+        }
+    }
+    Ok(open)
+}
+
+pub fn get_moderation_trail(item_address: Address) -> ZomeApiResult<ModerationTrail> {
+    let anchor_address = hdk::entry_address(&item_anchor_entry(&item_address))?;
+    let reports: Vec<Report> = hdk::utils::get_links_and_load_type(
+        &anchor_address,
+        LinkMatch::Exactly("reports"),
+        LinkMatch::Any,
+    )?;
+    let appeals: Vec<Appeal> = hdk::utils::get_links_and_load_type(
+        &anchor_address,
+        LinkMatch::Exactly("appeals"),
+        LinkMatch::Any,
+    )?;
+    Ok(ModerationTrail {
+        hidden: is_hidden(&item_address)?,
+        decisions: get_decisions(&item_address)?.into_iter().map(|(_, moderation)| moderation).collect(),
+        reports: reports.into_iter().filter(|report| report.item_address == item_address).collect(),
+        appeals: appeals.into_iter().filter(|appeal| appeal.item_address == item_address).collect(),
+        item_address,
+    })
+}
+
+/// Refuses hidden items, and anything inside a hidden course or module, to
+/// everyone but the course's teacher. `item_addresses` lists the course
+/// first, then the module and content being read.
+pub fn validate_visible(course: &Course, reader_address: &Address, item_addresses: &[&Address]) -> ZomeApiResult<()> {
+    if &course.teacher_address == reader_address {
+        return Ok(());
+    }
+    for item_address in item_addresses {
+        if is_hidden(item_address)? {
+            return Err(ZomeApiError::from(String::from("This item was taken down by a moderator")));
+        }
+    }
+    Ok(())
+}
+
+/// Drops hidden items from a public listing.
+pub fn filter_hidden(addresses: Vec<Address>) -> ZomeApiResult<Vec<Address>> {
+    let mut visible = Vec::new();
+    for address in addresses {
+        if !is_hidden(&address)? {
+            visible.push(address);
+        }
+    }
+    Ok(visible)
+}
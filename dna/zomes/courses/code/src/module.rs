@@ -1,33 +1,48 @@
 /************************ Import Required Libraries */
 use hdk::prelude::*;
 
+use crate::clock;
+use crate::course;
 use crate::course::Course;
+use crate::schedule;
+use hdk::holochain_core_types::time::Iso8601;
 use std::convert::TryFrom;
 /******************************************* */
 
 #[derive(Serialize, Deserialize, Debug, self::DefaultJson, Clone)]
 pub struct Module {
     pub title: String,
-    pub timestamp: u64,
+    pub created_at: Iso8601,
+    pub updated_at: Iso8601,
     pub course_address: Address,
+    pub release_offset: Option<u64>, // Seconds after the student's enrollment before the module opens
+    pub release_at: Option<u64>, // Unix time at which the module opens for everyone
 }
 
 impl Module {
-    pub fn new(title: String, course_address: Address, timestamp: u64) -> Self {
+    pub fn new(title: String, course_address: Address, created_at: Iso8601) -> Self {
         Module {
             title,
             course_address,
-            timestamp
+            created_at: created_at.clone(),
+            updated_at: created_at,
+            release_offset: None,
+            release_at: None,
         }
     }
 
     pub fn entry(&self) -> Entry {
         Entry::App("module".into(), self.into())
     }
+
+    pub fn touch(&mut self) -> ZomeApiResult<()> {
+        self.updated_at = clock::commit_time()?;
+        Ok(())
+    }
 }
 
 /****** Course Validations *****/
-fn validate_module_title(title: &str) -> Result<(), String> {
+pub fn validate_module_title(title: &str) -> Result<(), String> {
     if title.len() > 200 {
         Err("Module title is too long".into())
     } else {
@@ -41,6 +56,7 @@ fn validate_author(signing_addresses: &Vec<Address>, module: &Module) -> ZomeApi
     if !signing_addresses.contains(&course.teacher_address) {
         return Err(ZomeApiError::from(String::from("Only the teacher can modify a module for it")));
     }
+    course::validate_member(&course.teacher_address)?;
     Ok(())
 }
 
@@ -56,11 +72,20 @@ pub fn entry_def() -> ValidatingEntryType {
             match validation_data {
                 EntryValidationData::Create {entry, validation_data } => {
                     validate_module_title(&entry.title)?;
+                    schedule::validate_release(&entry)?;
+                    clock::validate_times(&validation_data.package.chain_header, &entry.created_at, &entry.updated_at, None)?;
                     validate_author(&validation_data.sources(), &entry)?;
                     Ok(())
                 },
                 EntryValidationData::Modify {new_entry, old_entry, validation_data, ..} => {
                     validate_module_title(&new_entry.title)?;
+                    schedule::validate_release(&new_entry)?;
+                    clock::validate_times(
+                        &validation_data.package.chain_header,
+                        &new_entry.created_at,
+                        &new_entry.updated_at,
+                        Some(&old_entry.created_at),
+                    )?;
 
                     if new_entry.course_address != old_entry.course_address {
                         return Err(String::from("Cannot modify the course of a module"));
@@ -89,14 +114,14 @@ pub fn entry_def() -> ValidatingEntryType {
     )
 }
 
-pub fn create(title: String, course_address: &Address, timestamp: u64) -> ZomeApiResult<Address> {
+pub fn create(title: String, course_address: &Address) -> ZomeApiResult<Address> {
     let mut course: Course = hdk::utils::get_as_type(course_address.to_string().into())?;
 
-    let new_module: Module = Module::new(title, course_address.to_string().into(), timestamp);
+    let new_module: Module = Module::new(title, course_address.to_string().into(), clock::commit_time()?);
     let new_module_address = hdk::commit_entry(&new_module.entry())?;
 
     course.modules.push(new_module_address.clone());
-    course.timestamp += 1;
+    course.touch()?;
     hdk::update_entry(course.entry(), &course_address)?;
 
     Ok(new_module_address)
@@ -105,6 +130,7 @@ pub fn create(title: String, course_address: &Address, timestamp: u64) -> ZomeAp
 pub fn update(title: String, module_address: &Address) -> ZomeApiResult<Address> {
     let mut module: Module = hdk::utils::get_as_type(module_address.to_string().into())?;
     module.title = title;
+    module.touch()?;
     hdk::update_entry(module.entry(), module_address)
 }
 
@@ -115,7 +141,7 @@ pub fn delete(module_address: Address) -> ZomeApiResult<Address> {
     let result = hdk::remove_entry(&module_address)?;
 
     course.modules.remove_item(&module_address);
-    course.timestamp += 1;
+    course.touch()?;
     hdk::update_entry(course.entry(), &module.course_address)?;
 
     Ok(result)
@@ -0,0 +1,294 @@
+/************************ Import Required Libraries */
+use hdk::prelude::*;
+
+use crate::clock;
+use crate::course;
+use crate::course::Course;
+use crate::prerequisite;
+use hdk::holochain_core_types::time::Iso8601;
+use hdk::AGENT_ADDRESS;
+use std::convert::TryFrom;
+/******************************************* */
+
+const MAX_TITLE_LENGTH: usize = 100;
+
+/// An ordered sequence of courses curated by a member.
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct LearningPath {
+    pub title: String,
+    pub courses: Vec<Address>, // In the order they should be taken
+    pub curator_address: Address,
+    pub created_at: Iso8601,
+    pub updated_at: Iso8601,
+}
+
+impl LearningPath {
+    pub fn new(title: String, courses: Vec<Address>, curator_address: Address, created_at: Iso8601) -> Self {
+        LearningPath {
+            title,
+            courses,
+            curator_address,
+            created_at: created_at.clone(),
+            updated_at: created_at,
+        }
+    }
+
+    pub fn entry(&self) -> Entry {
+        Entry::App("learning_path".into(), self.into())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct PathCourse {
+    pub course_address: Address,
+    pub title: String,
+    pub teacher_address: Address,
+    pub module_count: u32,
+    pub completed: bool, // By the caller
+}
+
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct PathDetails {
+    pub path_address: Address,
+    pub title: String,
+    pub curator_address: Address,
+    pub courses: Vec<PathCourse>,
+    pub completed: bool, // The caller has completed every course of the path
+}
+
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct SkippedCourse {
+    pub course_address: Address,
+    pub reason: String,
+}
+
+/// The cohort to join in one course of the path.
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct PathCohort {
+    pub course_address: Address,
+    pub cohort_address: Address,
+}
+
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct PathEnrollment {
+    pub enrolled: Vec<Address>, // Includes approval requests and waitlist places
+    pub skipped: Vec<SkippedCourse>, // Already enrolled courses are not listed
+}
+
+////////////////////Learning Path Entry Definition
+pub fn entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "learning_path",
+        description: "an ordered sequence of courses curated by a member",
+        sharing: Sharing::Public,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: | validation_data: hdk::EntryValidationData<LearningPath> | {
+            match validation_data {
+                EntryValidationData::Create {entry, validation_data} => {
+                    if !validation_data.sources().contains(&entry.curator_address) {
+                        return Err(String::from("A learning path must be signed by its curator"));
+                    }
+                    course::validate_member(&entry.curator_address)?;
+                    validate_path(&entry)?;
+                    clock::validate_times(&validation_data.package.chain_header, &entry.created_at, &entry.updated_at, None)?;
+                    Ok(())
+                },
+                EntryValidationData::Modify {new_entry, old_entry, validation_data, ..} => {
+                    if new_entry.curator_address != old_entry.curator_address {
+                        return Err(String::from("Cannot change the curator of a learning path"));
+                    }
+                    if !validation_data.sources().contains(&old_entry.curator_address) {
+                        return Err(String::from("Only the curator can edit a learning path"));
+                    }
+                    course::validate_member(&new_entry.curator_address)?;
+                    validate_path(&new_entry)?;
+                    clock::validate_times(
+                        &validation_data.package.chain_header,
+                        &new_entry.created_at,
+                        &new_entry.updated_at,
+                        Some(&old_entry.created_at),
+                    )?;
+                    Ok(())
+                },
+                EntryValidationData::Delete {..} => {
+                    Err(String::from("Learning paths cannot be deleted"))
+                }
+            }
+        },
+        links: [
+            from!(
+                "anchor",
+                link_type: "path_list",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, validation_data } => {
+                            validate_curator(link.link.target(), &validation_data.sources())?;
+                            Ok(())
+                        },
+                        hdk::LinkValidationData::LinkRemove { link, validation_data } => {
+                            validate_curator(link.link.target(), &validation_data.sources())?;
+                            Ok(())
+                        }
+                    }
+                }
+            ),
+            from!( // the paths a student has enrolled in
+                "%agent_id",
+                link_type: "student->paths",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, validation_data } => {
+                            if !validation_data.sources().contains(link.link.base()) {
+                                return Err(String::from("Students can only enrol themselves in a path"));
+                            }
+                            Ok(())
+                        },
+                        hdk::LinkValidationData::LinkRemove { .. } => Ok(())
+                    }
+                }
+            )
+        ]
+    )
+}
+
+/*********************** Learning Path Validations */
+fn validate_curator(path_address: &Address, signing_addresses: &Vec<Address>) -> ZomeApiResult<()> {
+    let path: LearningPath = hdk::utils::get_as_type(path_address.clone())?;
+    if !signing_addresses.contains(&path.curator_address) {
+        return Err(ZomeApiError::from(String::from(
+            "Only the curator can list a learning path",
+        )));
+    }
+    Ok(())
+}
+
+fn validate_path(path: &LearningPath) -> ZomeApiResult<()> {
+    if path.title.trim().is_empty() {
+        return Err(ZomeApiError::from(String::from("A learning path needs a title")));
+    }
+    if path.title.len() > MAX_TITLE_LENGTH {
+        return Err(ZomeApiError::from(String::from("Learning path title is too long")));
+    }
+    if path.courses.is_empty() {
+        return Err(ZomeApiError::from(String::from("A learning path needs at least one course")));
+    }
+    for (position, course_address) in path.courses.iter().enumerate() {
+        if path.courses[..position].contains(course_address) {
+            return Err(ZomeApiError::from(String::from("A course can only appear once in a learning path")));
+        }
+        hdk::utils::get_as_type::<Course>(course_address.clone())?;
+    }
+    Ok(())
+}
+
+/// Helper Functions
+fn anchor_entry() -> Entry {
+    Entry::App("anchor".into(), "learning_paths".into())
+}
+
+pub fn create_path(title: String, courses: Vec<Address>) -> ZomeApiResult<Address> {
+    let anchor_address = hdk::commit_entry(&anchor_entry())?;
+    let path = LearningPath::new(title, courses, AGENT_ADDRESS.to_string().into(), clock::commit_time()?);
+    let path_address = hdk::commit_entry(&path.entry())?;
+    hdk::link_entries(&anchor_address, &path_address, "path_list", "")?;
+    Ok(path_address)
+}
+
+pub fn update_path(path_address: Address, title: String, courses: Vec<Address>) -> ZomeApiResult<Address> {
+    let mut path: LearningPath = hdk::utils::get_as_type(path_address.clone())?;
+    path.title = title;
+    path.courses = courses;
+    path.updated_at = clock::commit_time()?;
+    hdk::update_entry(path.entry(), &path_address)
+}
+
+/// The path with a summary of each course and the caller's progress.
+pub fn get_path(path_address: Address) -> ZomeApiResult<PathDetails> {
+    let path: LearningPath = hdk::utils::get_as_type(path_address.clone())?;
+
+    let mut courses = Vec::new();
+    for course_address in path.courses {
+        let course: Course = hdk::utils::get_as_type(course_address.clone())?;
+        courses.push(PathCourse {
+            completed: prerequisite::has_completed(&course_address, &AGENT_ADDRESS)?,
+            course_address,
+            title: course.title,
+            teacher_address: course.teacher_address,
+            module_count: course.modules.len() as u32,
+        });
+    }
+
+    Ok(PathDetails {
+        path_address,
+        title: path.title,
+        curator_address: path.curator_address,
+        completed: courses.iter().all(|course| course.completed),
+        courses,
+    })
+}
+
+pub fn list_paths() -> ZomeApiResult<Vec<Address>> {
+    let links = hdk::get_links(
+        &hdk::entry_address(&anchor_entry())?,
+        LinkMatch::Exactly("path_list"),
+        LinkMatch::Any,
+    )?;
+    Ok(links.addresses())
+}
+
+pub fn get_my_paths() -> ZomeApiResult<Vec<Address>> {
+    let links = hdk::get_links(
+        &AGENT_ADDRESS,
+        LinkMatch::Exactly("student->paths"),
+        LinkMatch::Any,
+    )?;
+    Ok(links.addresses())
+}
+
+/// Enrols the caller in every course of the path that will take them now,
+/// in the cohort chosen in `cohorts` for courses that run cohorts. Courses
+/// that are priced, invite only, waiting on a prerequisite later in the path
+/// or need a cohort that was not chosen are reported as skipped; calling
+/// again once those are sorted out enrols in the rest.
+pub fn enrol_in_path(path_address: Address, cohorts: Vec<PathCohort>) -> ZomeApiResult<PathEnrollment> {
+    let path: LearningPath = hdk::utils::get_as_type(path_address.clone())?;
+    let student_address: Address = AGENT_ADDRESS.to_string().into();
+
+    if !get_my_paths()?.contains(&path_address) {
+        hdk::link_entries(&AGENT_ADDRESS, &path_address, "student->paths", "")?;
+    }
+
+    let mut enrollment = PathEnrollment {
+        enrolled: Vec::new(),
+        skipped: Vec::new(),
+    };
+    let enrolled_courses = course::get_my_enrolled_courses()?;
+    for course_address in path.courses {
+        if enrolled_courses.contains(&course_address) {
+            continue;
+        }
+        if prerequisite::has_completed(&course_address, &student_address)? {
+            continue;
+        }
+        let cohort_address = cohorts
+            .iter()
+            .find(|choice| choice.course_address == course_address)
+            .map(|choice| choice.cohort_address.clone());
+        match course::enrol_in_course(course_address.clone(), cohort_address) {
+            Ok(_) => enrollment.enrolled.push(course_address),
+            Err(error) => enrollment.skipped.push(SkippedCourse {
+                course_address,
+                reason: error.to_string(),
+            }),
+        }
+    }
+    Ok(enrollment)
+}
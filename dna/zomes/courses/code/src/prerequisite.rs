@@ -0,0 +1,247 @@
+/************************ Import Required Libraries */
+use hdk::prelude::*;
+
+use crate::course;
+use crate::course::Course;
+use hdk::AGENT_ADDRESS;
+use std::convert::TryFrom;
+/******************************************* */
+
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct Completion {
+    pub course_address: Address,
+    pub student_address: Address,
+    pub issued_by: Address,
+}
+
+impl Completion {
+    pub fn new(course_address: Address, student_address: Address, issued_by: Address) -> Self {
+        Completion {
+            course_address,
+            student_address,
+            issued_by,
+        }
+    }
+
+    pub fn entry(&self) -> Entry {
+        Entry::App("completion".into(), self.into())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct PrerequisiteEdge {
+    pub course_address: Address,
+    pub prerequisite_address: Address,
+}
+
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct PrerequisiteGraph {
+    pub root: Address,
+    pub courses: Vec<Address>,
+    pub edges: Vec<PrerequisiteEdge>,
+}
+
+////////////////////Completion Entry Definition
+pub fn entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "completion",
+        description: "a record issued by the teacher of a course once a student has completed it",
+        sharing: Sharing::Public,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: | validation_data: hdk::EntryValidationData<Completion> | {
+            match validation_data {
+                EntryValidationData::Create {entry, validation_data} => {
+                    if !validation_data.sources().contains(&entry.issued_by) {
+                        return Err(String::from("A completion must be signed by its issuer"));
+                    }
+                    let course: Course = hdk::utils::get_as_type(entry.course_address.clone())?;
+                    if course.teacher_address != entry.issued_by {
+                        return Err(String::from("Only the teacher can issue a completion for their course"));
+                    }
+                    Ok(())
+                },
+                EntryValidationData::Modify {..} => {
+                    Err(String::from("Cannot modify a completion"))
+                },
+                EntryValidationData::Delete {old_entry, validation_data, ..} => {
+                    let sources = validation_data.sources();
+                    if sources.contains(&old_entry.issued_by) {
+                        return Ok(());
+                    }
+                    let course: Course = hdk::utils::get_as_type(old_entry.course_address.clone())?;
+                    if !sources.contains(&course.teacher_address) {
+                        return Err(String::from("Only the issuer or the current teacher can revoke a completion"));
+                    }
+                    Ok(())
+                }
+            }
+        },
+        links: [
+            from!( // to query all the courses a student has completed
+                "%agent_id",
+                link_type: "student->completions",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, validation_data } => {
+                            let completion: Completion = hdk::utils::get_as_type(link.link.target().clone())?;
+                            if &completion.student_address != link.link.base() {
+                                return Err(String::from("A completion can only be linked from its student"));
+                            }
+                            if !validation_data.sources().contains(&completion.issued_by) {
+                                return Err(String::from("Only the issuer can link a completion"));
+                            }
+                            Ok(())
+                        },
+                        hdk::LinkValidationData::LinkRemove { .. } => {
+                            Err(String::from("Completions are revoked by deleting them, not their link"))
+                        }
+                    }
+                }
+            )
+        ]
+    )
+}
+
+/*********************** Prerequisite Validations */
+
+/// Walks the prerequisites of `course_address` and fails if the course can be
+/// reached from them again, so the prerequisites always form a DAG.
+pub fn validate_prerequisites(course_address: &Address, prerequisites: &Vec<Address>) -> ZomeApiResult<()> {
+    let mut visited: Vec<Address> = Vec::new();
+    let mut pending = prerequisites.clone();
+
+    while let Some(address) = pending.pop() {
+        if &address == course_address {
+            return Err(ZomeApiError::from(String::from(
+                "Course prerequisites cannot form a cycle",
+            )));
+        }
+        if visited.contains(&address) {
+            continue;
+        }
+        let prerequisite: Course = hdk::utils::get_as_type(address.clone())?;
+        pending.extend(prerequisite.prerequisites);
+        visited.push(address);
+    }
+    Ok(())
+}
+
+/// Helper Functions
+
+/// Completions keep counting after a course changes teacher, so they are
+/// found through the student's links and accepted from anyone who has taught
+/// the course, not only its current teacher.
+pub fn has_completed(course_address: &Address, student_address: &Address) -> ZomeApiResult<bool> {
+    let links = hdk::get_links(
+        student_address,
+        LinkMatch::Exactly("student->completions"),
+        LinkMatch::Any,
+    )?;
+    let mut teachers: Option<Vec<Address>> = None;
+    for completion_address in links.addresses() {
+        // revoked completions are deleted but keep their link
+        if let Some(Entry::App(_, value)) = hdk::get_entry(&completion_address)? {
+            let completion = Completion::try_from(value)?;
+            if &completion.course_address != course_address || &completion.student_address != student_address {
+                continue;
+            }
+            if teachers.is_none() {
+                teachers = Some(course::get_teachers(course_address)?);
+            }
+            if teachers.as_ref().map_or(false, |teachers| teachers.contains(&completion.issued_by)) {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// The issuer or the course's current teacher can revoke a completion.
+pub fn revoke_completion(completion_address: Address) -> ZomeApiResult<Address> {
+    let completion: Completion = hdk::utils::get_as_type(completion_address.clone())?;
+    let course: Course = hdk::utils::get_as_type(completion.course_address.clone())?;
+    let agent_address = Address::from(AGENT_ADDRESS.to_string());
+    if completion.issued_by != agent_address && course.teacher_address != agent_address {
+        return Err(ZomeApiError::from(String::from(
+            "Only the issuer or the current teacher can revoke a completion",
+        )));
+    }
+    hdk::remove_entry(&completion_address)
+}
+
+pub fn get_missing_prerequisites(course: &Course, student_address: &Address) -> ZomeApiResult<Vec<Address>> {
+    let mut missing = Vec::new();
+    for prerequisite_address in &course.prerequisites {
+        if !has_completed(prerequisite_address, student_address)? {
+            missing.push(prerequisite_address.clone());
+        }
+    }
+    Ok(missing)
+}
+
+pub fn validate_student_prerequisites(course: &Course, student_address: &Address) -> ZomeApiResult<()> {
+    let missing = get_missing_prerequisites(course, student_address)?;
+    if !missing.is_empty() {
+        let missing: Vec<String> = missing.iter().map(|address| address.to_string()).collect();
+        return Err(ZomeApiError::from(format!(
+            "Missing prerequisites: {}",
+            missing.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+pub fn update_prerequisites(course_address: Address, prerequisites: Vec<Address>) -> ZomeApiResult<Address> {
+    let mut course: Course = hdk::utils::get_as_type(course_address.clone())?;
+    course.prerequisites = prerequisites;
+    course.touch()?;
+    hdk::update_entry(course.entry(), &course_address)
+}
+
+pub fn issue_completion(course_address: Address, student_address: Address) -> ZomeApiResult<Address> {
+    let completion = Completion::new(course_address, student_address.clone(), AGENT_ADDRESS.to_string().into());
+    let completion_address = hdk::commit_entry(&completion.entry())?;
+    hdk::link_entries(&student_address, &completion_address, "student->completions", "")?;
+    Ok(completion_address)
+}
+
+pub fn get_my_completions() -> ZomeApiResult<Vec<Address>> {
+    let links = hdk::get_links(
+        &AGENT_ADDRESS,
+        LinkMatch::Exactly("student->completions"),
+        LinkMatch::Any,
+    )?;
+    Ok(links.addresses())
+}
+
+pub fn get_prerequisite_graph(course_address: Address) -> ZomeApiResult<PrerequisiteGraph> {
+    let mut courses: Vec<Address> = Vec::new();
+    let mut edges: Vec<PrerequisiteEdge> = Vec::new();
+    let mut pending = vec![course_address.clone()];
+
+    while let Some(address) = pending.pop() {
+        if courses.contains(&address) {
+            continue;
+        }
+        let course: Course = hdk::utils::get_as_type(address.clone())?;
+        for prerequisite_address in course.prerequisites {
+            edges.push(PrerequisiteEdge {
+                course_address: address.clone(),
+                prerequisite_address: prerequisite_address.clone(),
+            });
+            pending.push(prerequisite_address);
+        }
+        courses.push(address);
+    }
+
+    Ok(PrerequisiteGraph {
+        root: course_address,
+        courses,
+        edges,
+    })
+}
@@ -0,0 +1,150 @@
+/************************ Import Required Libraries */
+use hdk::prelude::*;
+
+use crate::cohort;
+use crate::course;
+use crate::course::Course;
+use crate::enrollment;
+use crate::enrollment::EnrollmentPolicy;
+use crate::invite;
+use crate::prerequisite;
+use crate::waitlist;
+use hdk::AGENT_ADDRESS;
+use serde_json::json;
+use std::convert::TryFrom;
+/******************************************* */
+
+/// Mirror of the `ledger` zome's `Transaction` entry, read back to check
+/// that an enrollment in a priced course has been paid for.
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct Payment {
+    pub spender_address: Address,
+    pub receiver_address: Address,
+    pub amount: u64,
+    pub reference: String,
+    pub sequence: u32,
+}
+
+pub fn payment_reference(course_address: &Address) -> String {
+    format!("course:{}", course_address)
+}
+
+/*********************** Purchase Validations */
+
+/// Called from the `course->students` link validation: in a priced course the
+/// link tag must be the address of the student's payment to the teacher.
+pub fn validate_payment(course_address: &Address, student_address: &Address, tag: &str) -> ZomeApiResult<()> {
+    let course: Course = hdk::utils::get_as_type(course_address.clone())?;
+    let price = match course.price {
+        Some(price) => price,
+        None => return Ok(()),
+    };
+
+    let payment: Payment = hdk::utils::get_as_type(Address::from(tag))
+        .map_err(|_| ZomeApiError::from(String::from("Enrollment in a priced course needs a payment")))?;
+
+    if &payment.spender_address != student_address
+        || payment.receiver_address != course.teacher_address
+        || payment.reference != payment_reference(course_address)
+        || payment.amount < price
+        || payment.amount > i64::MAX as u64
+    {
+        return Err(ZomeApiError::from(String::from(
+            "Payment does not match the course price",
+        )));
+    }
+    Ok(())
+}
+
+/// Helper Functions
+fn pay(receiver_address: &Address, amount: u64, reference: String) -> ZomeApiResult<Address> {
+    let transaction_json: JsonString = hdk::call(
+        hdk::THIS_INSTANCE,
+        "ledger",
+        Address::from(hdk::PUBLIC_TOKEN.to_string()),
+        "transfer",
+        json!({"receiver_address": receiver_address, "amount": amount, "reference": reference}).into(),
+    )?;
+
+    let transaction_address: Result<ZomeApiResult<Address>, _> = serde_json::from_str(&transaction_json.to_string());
+
+    match transaction_address {
+        Ok(Ok(address)) => Ok(address),
+        Ok(Err(error)) => Err(error),
+        Err(_) => Err(ZomeApiError::from(String::from("Could not record the payment"))),
+    }
+}
+
+/// The student's own payment for the course, made earlier on their chain.
+/// Paying and linking happen in separate commits, so a purchase that was paid
+/// but never linked is finished with the payment instead of charging again.
+fn find_payment(course_address: &Address, course: &Course, price: u64) -> ZomeApiResult<Option<Address>> {
+    Ok(invite::query_app_entries::<Payment>("transaction")?
+        .into_iter()
+        .find(|(_, payment)| {
+            payment.spender_address == *AGENT_ADDRESS
+                && payment.receiver_address == course.teacher_address
+                && payment.reference == payment_reference(course_address)
+                && payment.amount >= price
+        })
+        .map(|(address, _)| address))
+}
+
+/// Returns the address of a payment covering the course, paying the teacher
+/// only if the student has not already done so.
+pub fn find_or_pay(course_address: &Address, course: &Course) -> ZomeApiResult<Address> {
+    let price = course
+        .price
+        .ok_or_else(|| ZomeApiError::from(String::from("This course is free")))?;
+    match find_payment(course_address, course, price)? {
+        Some(payment_address) => Ok(payment_address),
+        None => pay(&course.teacher_address, price, payment_reference(course_address)),
+    }
+}
+
+pub fn update_price(course_address: Address, price: Option<u64>) -> ZomeApiResult<Address> {
+    let mut course: Course = hdk::utils::get_as_type(course_address.clone())?;
+    course.price = price;
+    course.touch()?;
+    hdk::update_entry(course.entry(), &course_address)
+}
+
+/// Checks everything enrollment needs before paying, so the buyer is never
+/// charged for a seat they cannot take, then pays the teacher and enrolls.
+/// If enrolling fails after the payment, calling this again reuses it.
+pub fn purchase_course(course_address: Address, cohort_address: Option<Address>) -> ZomeApiResult<Address> {
+    let course: Course = hdk::utils::get_as_type(course_address.clone())?;
+    if course.price.is_none() {
+        return Err(ZomeApiError::from(String::from("This course is free, enrol in it instead")));
+    }
+
+    if course::get_students(course_address.clone())?.contains(&AGENT_ADDRESS) {
+        return Err(ZomeApiError::from(String::from("You are already enrolled in this course")));
+    }
+    match course.enrollment_policy {
+        EnrollmentPolicy::Open => (),
+        EnrollmentPolicy::ApprovalRequired => {
+            if !enrollment::is_approved_for(&course_address, &AGENT_ADDRESS, &cohort_address)? {
+                return Err(ZomeApiError::from(String::from(
+                    "Enrollment has not been approved by the teacher",
+                )));
+            }
+        }
+        EnrollmentPolicy::InviteOnly => {
+            return Err(ZomeApiError::from(String::from("This course is invite only")))
+        }
+    }
+    prerequisite::validate_student_prerequisites(&course, &AGENT_ADDRESS)?;
+    cohort::check_selection(&course_address, &cohort_address)?;
+    if waitlist::is_full(&course, &course_address)? {
+        return Err(ZomeApiError::from(String::from("Course is full")));
+    }
+
+    let transaction_address = find_or_pay(&course_address, &course)?;
+
+    hdk::link_entries(&AGENT_ADDRESS, &course_address, "student->courses", "")?;
+    let link_address = hdk::link_entries(&course_address, &AGENT_ADDRESS, "course->students", transaction_address.to_string().as_str())?;
+    cohort::join(&cohort_address, &AGENT_ADDRESS)?;
+    waitlist::leave(&course_address, &AGENT_ADDRESS)?;
+    Ok(link_address)
+}
@@ -0,0 +1,159 @@
+/************************ Import Required Libraries */
+use hdk::prelude::*;
+
+use crate::clock;
+use crate::course::Course;
+use crate::moderation;
+use crate::module::Module;
+use hdk::holochain_core_types::time::Iso8601;
+use hdk::AGENT_ADDRESS;
+use std::time::Duration;
+/******************************************* */
+
+/// The last second of the year 9999, the latest time a release can be read as.
+const MAX_RELEASE_SECS: u64 = 253_402_300_799;
+
+/*********************** Release Validations */
+pub fn validate_release(module: &Module) -> Result<(), String> {
+    validate_release_times(&module.release_offset, &module.release_at)
+}
+
+pub fn validate_release_times(release_offset: &Option<u64>, release_at: &Option<u64>) -> Result<(), String> {
+    if release_offset.is_some() && release_at.is_some() {
+        return Err(String::from(
+            "A module is released either relative to enrollment or at a fixed date, not both",
+        ));
+    }
+    if release_offset.unwrap_or(0) > MAX_RELEASE_SECS || release_at.unwrap_or(0) > MAX_RELEASE_SECS {
+        return Err(String::from("Release time is too far in the future"));
+    }
+    Ok(())
+}
+
+/// Helper Functions
+
+/// When the link putting the student in the course was committed.
+pub fn enrolled_at(course_address: &Address, student_address: &Address) -> ZomeApiResult<Option<Iso8601>> {
+    let links = hdk::get_links_with_options(
+        course_address,
+        LinkMatch::Exactly("course->students"),
+        LinkMatch::Any,
+        GetLinksOptions {
+            headers: true,
+            ..GetLinksOptions::default()
+        },
+    )?;
+    Ok(links
+        .links()
+        .into_iter()
+        .filter(|link| &link.address == student_address)
+        .filter_map(|link| link.headers.first().map(|header| header.timestamp().clone()))
+        .min())
+}
+
+/// The time the module opens for `student_address`, or `None` when it is
+/// scheduled relative to an enrollment the student does not have.
+fn release_time(module: &Module, student_address: &Address) -> ZomeApiResult<Option<Iso8601>> {
+    if let Some(release_at) = module.release_at {
+        return Ok(Some(Iso8601::from(release_at)));
+    }
+    match module.release_offset {
+        Some(release_offset) => match enrolled_at(&module.course_address, student_address)? {
+            Some(enrolled) => (enrolled + Duration::from_secs(release_offset))
+                .map(Some)
+                .map_err(|error| ZomeApiError::from(error.to_string())),
+            None => Ok(None),
+        },
+        None => Ok(Some(Iso8601::from(0u64))),
+    }
+}
+
+/// The teacher always sees every module; students only once it is released.
+/// Listings read `now` once and pass it to every check.
+pub fn is_released(module: &Module, student_address: &Address, now: &Iso8601) -> ZomeApiResult<bool> {
+    if module.release_offset.is_none() && module.release_at.is_none() {
+        return Ok(true);
+    }
+    let course: Course = hdk::utils::get_as_type(module.course_address.clone())?;
+    if &course.teacher_address == student_address {
+        return Ok(true);
+    }
+    match release_time(module, student_address)? {
+        Some(release_time) => Ok(now >= &release_time),
+        None => Ok(false),
+    }
+}
+
+/// The time read paths check releases against. `clock::now` lags behind while
+/// the agent only reads, so the first module that still looks locked is
+/// checked again at the time of a fresh tick. Reads that find nothing waiting
+/// commit nothing.
+pub struct ReleaseClock {
+    now: Iso8601,
+    refreshed: bool,
+}
+
+impl ReleaseClock {
+    pub fn new() -> ZomeApiResult<Self> {
+        Ok(ReleaseClock {
+            now: clock::now()?,
+            refreshed: false,
+        })
+    }
+
+    pub fn is_released(&mut self, module: &Module, student_address: &Address) -> ZomeApiResult<bool> {
+        if is_released(module, student_address, &self.now)? {
+            return Ok(true);
+        }
+        if self.refreshed || release_time(module, student_address)?.is_none() {
+            return Ok(false);
+        }
+        self.now = clock::commit_time()?;
+        self.refreshed = true;
+        is_released(module, student_address, &self.now)
+    }
+}
+
+pub fn validate_released(module: &Module, student_address: &Address) -> ZomeApiResult<()> {
+    if !ReleaseClock::new()?.is_released(module, student_address)? {
+        return Err(ZomeApiError::from(String::from("This module is not released yet")));
+    }
+    Ok(())
+}
+
+pub fn get_module(module_address: Address) -> ZomeApiResult<Module> {
+    let module: Module = hdk::utils::get_as_type(module_address.clone())?;
+    let course: Course = hdk::utils::get_as_type(module.course_address.clone())?;
+    moderation::validate_visible(&course, &AGENT_ADDRESS, &[&module.course_address, &module_address])?;
+    validate_released(&module, &AGENT_ADDRESS)?;
+    Ok(module)
+}
+
+pub fn get_modules(course_address: Address) -> ZomeApiResult<Vec<Address>> {
+    let course: Course = hdk::utils::get_as_type(course_address)?;
+    if course.teacher_address == Address::from(AGENT_ADDRESS.to_string()) {
+        return Ok(course.modules);
+    }
+    moderation::filter_hidden(released_modules(&course, &AGENT_ADDRESS)?)
+}
+
+/// The modules of `course` that are open to `student_address`, in course order.
+pub fn released_modules(course: &Course, student_address: &Address) -> ZomeApiResult<Vec<Address>> {
+    let mut clock = ReleaseClock::new()?;
+    let mut released = Vec::new();
+    for module_address in &course.modules {
+        let module: Module = hdk::utils::get_as_type(module_address.clone())?;
+        if clock.is_released(&module, student_address)? {
+            released.push(module_address.clone());
+        }
+    }
+    Ok(released)
+}
+
+pub fn update_release(module_address: Address, release_offset: Option<u64>, release_at: Option<u64>) -> ZomeApiResult<Address> {
+    let mut module: Module = hdk::utils::get_as_type(module_address.clone())?;
+    module.release_offset = release_offset;
+    module.release_at = release_at;
+    module.touch()?;
+    hdk::update_entry(module.entry(), &module_address)
+}
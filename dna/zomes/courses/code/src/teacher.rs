@@ -0,0 +1,62 @@
+/************************ Import Required Libraries */
+use hdk::prelude::*;
+
+use crate::course;
+use crate::course::Course;
+use std::convert::TryFrom;
+/******************************************* */
+
+/// What learners see when browsing a teacher's page.
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct TeacherSummary {
+    pub teacher_address: Address,
+    pub profile: Option<serde_json::Value>, // As returned by the members zome
+    pub courses: Vec<Address>, // Published courses, deleted ones are left out
+    pub total_students: u32, // Distinct students across all their courses
+    pub average_rating: Option<f32>, // Always None until courses can be rated
+}
+
+/// Helper Functions
+fn get_profile(teacher_address: &Address) -> ZomeApiResult<Option<serde_json::Value>> {
+    let profile_json: JsonString = hdk::call(
+        hdk::THIS_INSTANCE,
+        "members",
+        Address::from(hdk::PUBLIC_TOKEN.to_string()),
+        "get_profile",
+        json!({"agent_address": teacher_address}).into(),
+    )?;
+
+    let profile: Result<ZomeApiResult<Option<serde_json::Value>>, _> = serde_json::from_str(&profile_json.to_string());
+
+    match profile {
+        Ok(Ok(profile)) => Ok(profile),
+        _ => Err(ZomeApiError::from(String::from(
+            "Could not get the profile of this teacher",
+        ))),
+    }
+}
+
+pub fn get_teacher_summary(teacher_address: Address) -> ZomeApiResult<TeacherSummary> {
+    // Deleted courses keep their teacher link but no longer load
+    let courses: Vec<Address> = course::get_teacher_courses(teacher_address.clone())?
+        .into_iter()
+        .filter(|course_address| hdk::utils::get_as_type::<Course>(course_address.clone()).is_ok())
+        .collect();
+
+    let mut students: Vec<Address> = Vec::new();
+    for course_address in &courses {
+        for student in course::get_students(course_address.clone())? {
+            if !students.contains(&student) {
+                students.push(student);
+            }
+        }
+    }
+
+    Ok(TeacherSummary {
+        profile: get_profile(&teacher_address)?,
+        teacher_address,
+        courses,
+        total_students: students.len() as u32,
+        average_rating: None,
+    })
+}
@@ -0,0 +1,240 @@
+/************************ Import Required Libraries */
+use hdk::prelude::*;
+
+use crate::course;
+use crate::course::Course;
+use crate::encryption;
+use hdk::AGENT_ADDRESS;
+use std::convert::TryFrom;
+/******************************************* */
+
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct CourseTransfer {
+    pub course_address: Address,
+    pub course_version: Address, // The entry the course was at when proposed, so it hands over that version only
+    pub from_teacher: Address,
+    pub to_teacher: Address,
+}
+
+impl CourseTransfer {
+    pub fn new(course_address: Address, course_version: Address, from_teacher: Address, to_teacher: Address) -> Self {
+        CourseTransfer {
+            course_address,
+            course_version,
+            from_teacher,
+            to_teacher,
+        }
+    }
+
+    pub fn entry(&self) -> Entry {
+        Entry::App("course_transfer".into(), self.into())
+    }
+}
+
+////////////////////Course Transfer Entry Definition
+pub fn entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "course_transfer",
+        description: "a proposal from the teacher of a course to hand it over to another member",
+        sharing: Sharing::Public,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: | validation_data: hdk::EntryValidationData<CourseTransfer> | {
+            match validation_data {
+                EntryValidationData::Create {entry, validation_data} => {
+                    if !validation_data.sources().contains(&entry.from_teacher) {
+                        return Err(String::from("Only the teacher can propose a transfer of their course"));
+                    }
+                    if entry.from_teacher == entry.to_teacher {
+                        return Err(String::from("Cannot transfer a course to its own teacher"));
+                    }
+                    let course: Course = hdk::utils::get_as_type(entry.course_address.clone())?;
+                    if course.teacher_address != entry.from_teacher {
+                        return Err(String::from("Only the current teacher can transfer a course"));
+                    }
+                    course::validate_teacher_role(&entry.to_teacher)?;
+                    Ok(())
+                },
+                EntryValidationData::Modify {..} => {
+                    Err(String::from("Cannot modify a course transfer"))
+                },
+                EntryValidationData::Delete {old_entry, validation_data, ..} => {
+                    let sources = validation_data.sources();
+                    if !sources.contains(&old_entry.from_teacher) && !sources.contains(&old_entry.to_teacher) {
+                        return Err(String::from("Only the teachers involved can delete a course transfer"));
+                    }
+                    Ok(())
+                }
+            }
+        },
+        links: [
+            from!( // to query the pending transfers of a course
+                "course",
+                link_type: "course->transfers",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | _validation_data: hdk::LinkValidationData | {
+                    Ok(())
+                }
+            ),
+            from!( // to query the transfers a member has been offered
+                "%agent_id",
+                link_type: "teacher->incoming_transfers",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | _validation_data: hdk::LinkValidationData | {
+                    Ok(())
+                }
+            )
+        ]
+    )
+}
+
+/*********************** Transfer Validations */
+
+/// Called from the course `Modify` validation when `teacher_address` changes:
+/// the update must be signed by the new teacher, and a live transfer of the
+/// replaced version signed by the old teacher must exist. Transfers record
+/// the address the course was first committed at, so that is what
+/// `course_address` is.
+pub fn validate_transfer(
+    course_address: &Address,
+    course_version: &Address,
+    old_course: &Course,
+    new_course: &Course,
+    signing_addresses: &Vec<Address>,
+) -> ZomeApiResult<()> {
+    if !signing_addresses.contains(&new_course.teacher_address) {
+        return Err(ZomeApiError::from(String::from(
+            "Only the new teacher can accept a course transfer",
+        )));
+    }
+
+    let transfer = CourseTransfer::new(
+        course_address.clone(),
+        course_version.clone(),
+        old_course.teacher_address.clone(),
+        new_course.teacher_address.clone(),
+    );
+    let transfer_address = hdk::entry_address(&transfer.entry())?;
+
+    // accepted and cancelled transfers are deleted
+    if hdk::get_entry(&transfer_address)?.is_none() {
+        return Err(ZomeApiError::from(String::from(
+            "Course transfer has been accepted or cancelled",
+        )));
+    }
+    if !get_signers(&transfer_address)?.contains(&old_course.teacher_address) {
+        return Err(ZomeApiError::from(String::from(
+            "Course transfer has not been signed by the current teacher",
+        )));
+    }
+    Ok(())
+}
+
+fn get_signers(address: &Address) -> ZomeApiResult<Vec<Address>> {
+    let result = hdk::get_entry_result(
+        address,
+        GetEntryOptions::new(StatusRequestKind::Initial, false, true, Default::default()),
+    )?;
+
+    match result.result {
+        GetEntryResultType::Single(item) => Ok(item
+            .headers
+            .iter()
+            .flat_map(|header| header.provenances().iter().map(|p| p.source()))
+            .collect()),
+        _ => Ok(Vec::new()),
+    }
+}
+
+/// Helper Functions
+fn current_version(course_address: &Address) -> ZomeApiResult<Address> {
+    let course: Course = hdk::utils::get_as_type(course_address.clone())?;
+    hdk::entry_address(&course.entry())
+}
+
+pub fn propose(course_address: Address, new_teacher_address: Address) -> ZomeApiResult<Address> {
+    let transfer = CourseTransfer::new(
+        course_address.clone(),
+        current_version(&course_address)?,
+        AGENT_ADDRESS.to_string().into(),
+        new_teacher_address.clone(),
+    );
+    let transfer_address = hdk::commit_entry(&transfer.entry())?;
+
+    hdk::link_entries(&course_address, &transfer_address, "course->transfers", "")?;
+    hdk::link_entries(&new_teacher_address, &transfer_address, "teacher->incoming_transfers", "")?;
+
+    Ok(transfer_address)
+}
+
+/// Whether this node, as the teacher, has offered the course to `agent_address`.
+pub fn is_offered_to(course_address: &Address, agent_address: &Address) -> ZomeApiResult<bool> {
+    let links = hdk::get_links(
+        course_address,
+        LinkMatch::Exactly("course->transfers"),
+        LinkMatch::Any,
+    )?;
+    for transfer_address in links.addresses() {
+        let transfer: CourseTransfer = hdk::utils::get_as_type(transfer_address)?;
+        if &transfer.to_teacher == agent_address && transfer.from_teacher == Address::from(AGENT_ADDRESS.to_string()) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+pub fn get_my_transfer_proposals() -> ZomeApiResult<Vec<Address>> {
+    let links = hdk::get_links(
+        &AGENT_ADDRESS,
+        LinkMatch::Exactly("teacher->incoming_transfers"),
+        LinkMatch::Any,
+    )?;
+    Ok(links.addresses())
+}
+
+/// Takes the course over and uses the transfer up. The course must not have
+/// changed since the transfer was proposed. Grades stay on the previous
+/// teacher's chain (see `gradebook::GradeItem`).
+pub fn accept(transfer_address: Address) -> ZomeApiResult<Address> {
+    let transfer: CourseTransfer = hdk::utils::get_as_type(transfer_address.clone())?;
+    if transfer.to_teacher != Address::from(AGENT_ADDRESS.to_string()) {
+        return Err(ZomeApiError::from(String::from(
+            "This course transfer was not offered to you",
+        )));
+    }
+    if current_version(&transfer.course_address)? != transfer.course_version {
+        return Err(ZomeApiError::from(String::from(
+            "The course has changed since this transfer was proposed",
+        )));
+    }
+
+    let updated_address = encryption::hand_over(&transfer.course_address, &transfer.to_teacher)?;
+
+    hdk::remove_link(&transfer.from_teacher, &transfer.course_address, "teacher->courses", "")?;
+    hdk::link_entries(&AGENT_ADDRESS, &transfer.course_address, "teacher->courses", "")?;
+
+    withdraw(&transfer_address, &transfer)?;
+    Ok(updated_address)
+}
+
+/// Lets the teacher take back a transfer that has not been accepted yet.
+pub fn cancel(transfer_address: Address) -> ZomeApiResult<Address> {
+    let transfer: CourseTransfer = hdk::utils::get_as_type(transfer_address.clone())?;
+    if transfer.from_teacher != Address::from(AGENT_ADDRESS.to_string()) {
+        return Err(ZomeApiError::from(String::from(
+            "Only the teacher who proposed a transfer can cancel it",
+        )));
+    }
+    withdraw(&transfer_address, &transfer)
+}
+
+fn withdraw(transfer_address: &Address, transfer: &CourseTransfer) -> ZomeApiResult<Address> {
+    hdk::remove_link(&transfer.course_address, transfer_address, "course->transfers", "")?;
+    hdk::remove_link(&transfer.to_teacher, transfer_address, "teacher->incoming_transfers", "")?;
+    hdk::remove_entry(transfer_address)
+}
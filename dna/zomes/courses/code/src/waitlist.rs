@@ -0,0 +1,150 @@
+/************************ Import Required Libraries */
+use hdk::prelude::*;
+
+use crate::cohort;
+use crate::course;
+use crate::course::Course;
+use crate::message;
+use crate::message::DirectMessage;
+use hdk::AGENT_ADDRESS;
+/******************************************* */
+
+/*********************** Capacity Validations */
+
+/// Called from the `course->students` link validation, so the seat cap holds
+/// no matter which zome fn (or which node) created the link.
+pub fn validate_capacity(course_address: &Address, student_address: &Address) -> ZomeApiResult<()> {
+    let course: Course = hdk::utils::get_as_type(course_address.clone())?;
+    if let Some(max_students) = course.max_students {
+        let students = course::get_students(course_address.clone())?;
+        let taken = students.iter().filter(|student| *student != student_address).count();
+        if taken >= max_students as usize {
+            return Err(ZomeApiError::from(String::from("Course is full")));
+        }
+    }
+    Ok(())
+}
+
+pub fn validate_max_students(max_students: &Option<u32>) -> Result<(), String> {
+    match max_students {
+        Some(0) => Err("A course needs at least one seat".into()),
+        _ => Ok(()),
+    }
+}
+
+/// Students put themselves on the waitlist and take themselves off it; the
+/// teacher can take anyone off when promoting them.
+pub fn validate_waitlist_change(course_address: &Address, student_address: &Address, signing_addresses: &Vec<Address>) -> ZomeApiResult<()> {
+    if signing_addresses.contains(student_address) {
+        return Ok(());
+    }
+    let course: Course = hdk::utils::get_as_type(course_address.clone())?;
+    if !signing_addresses.contains(&course.teacher_address) {
+        return Err(ZomeApiError::from(String::from(
+            "Only the student or the teacher can change a place on the waitlist",
+        )));
+    }
+    Ok(())
+}
+
+/// Helper Functions
+pub fn is_full(course: &Course, course_address: &Address) -> ZomeApiResult<bool> {
+    match course.max_students {
+        Some(max_students) => Ok(course::get_students(course_address.clone())?.len() >= max_students as usize),
+        None => Ok(false),
+    }
+}
+
+/// The waitlist link is tagged with the cohort the student asked for.
+pub fn join(course_address: &Address, cohort_address: &Option<Address>) -> ZomeApiResult<Address> {
+    if get_waitlist(course_address.clone())?.contains(&AGENT_ADDRESS) {
+        return Err(ZomeApiError::from(String::from("You are already on the waitlist")));
+    }
+    if course::get_students(course_address.clone())?.contains(&AGENT_ADDRESS) {
+        return Err(ZomeApiError::from(String::from("You are already enrolled in this course")));
+    }
+    hdk::link_entries(course_address, &AGENT_ADDRESS, "course->waitlist", cohort_tag(cohort_address).as_str())
+}
+
+pub fn leave(course_address: &Address, student_address: &Address) -> ZomeApiResult<()> {
+    for (waiting_address, cohort_address) in get_waiting(course_address)? {
+        if &waiting_address == student_address {
+            hdk::remove_link(course_address, student_address, "course->waitlist", cohort_tag(&cohort_address).as_str())?;
+        }
+    }
+    Ok(())
+}
+
+fn cohort_tag(cohort_address: &Option<Address>) -> String {
+    cohort_address.as_ref().map(|address| address.to_string()).unwrap_or_default()
+}
+
+/// The students waiting for a seat, oldest first, with the cohort each asked for.
+fn get_waiting(course_address: &Address) -> ZomeApiResult<Vec<(Address, Option<Address>)>> {
+    let links = hdk::get_links_with_options(
+        course_address,
+        LinkMatch::Exactly("course->waitlist"),
+        LinkMatch::Any,
+        GetLinksOptions {
+            headers: true,
+            ..GetLinksOptions::default()
+        },
+    )?;
+
+    let mut waiting = links.links();
+    waiting.sort_by_key(|link| link.headers.first().map(|header| header.timestamp().clone()));
+    Ok(waiting
+        .into_iter()
+        .map(|link| {
+            let cohort_address = if link.tag.is_empty() { None } else { Some(Address::from(link.tag)) };
+            (link.address, cohort_address)
+        })
+        .collect())
+}
+
+pub fn get_waitlist(course_address: Address) -> ZomeApiResult<Vec<Address>> {
+    Ok(get_waiting(&course_address)?
+        .into_iter()
+        .map(|(student_address, _)| student_address)
+        .collect())
+}
+
+/// Runs on the teacher's node: moves the first student on the waitlist into
+/// the course, if a seat is free, and lets them know through a direct message.
+/// In a priced course the seat is only offered, as the student takes it by
+/// purchasing the course.
+pub fn promote_next(course_address: &Address) -> ZomeApiResult<Option<Address>> {
+    let course: Course = hdk::utils::get_as_type(course_address.clone())?;
+    if course.teacher_address != Address::from(AGENT_ADDRESS.to_string()) {
+        return Err(ZomeApiError::from(String::from(
+            "Only the teacher can promote students from the waitlist",
+        )));
+    }
+    if is_full(&course, course_address)? {
+        return Ok(None);
+    }
+
+    for (student_address, cohort_address) in get_waiting(course_address)? {
+        // students whose cohort filled up in the meantime keep waiting
+        if cohort::check_selection(course_address, &cohort_address).is_err() {
+            continue;
+        }
+        let enrolled = course.price.is_none();
+        if enrolled {
+            hdk::remove_link(course_address, &student_address, "course->waitlist", cohort_tag(&cohort_address).as_str())?;
+            hdk::link_entries(&student_address, course_address, "student->courses", "")?;
+            hdk::link_entries(course_address, &student_address, "course->students", "")?;
+            cohort::join(&cohort_address, &student_address)?;
+        }
+
+        let promotion = DirectMessage::WaitlistPromotion {
+            course_address: course_address.clone(),
+            enrolled,
+        };
+        if let Err(error) = message::send::<serde_json::Value>(student_address.clone(), promotion) {
+            hdk::debug(format!("Could not notify promoted student: {}", error))?;
+        }
+        return Ok(if enrolled { Some(student_address) } else { None });
+    }
+    Ok(None)
+}
@@ -0,0 +1,63 @@
+#![feature(proc_macro_hygiene)]
+#[macro_use]
+extern crate hdk;
+extern crate hdk_proc_macros;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
+extern crate serde_json;
+#[macro_use]
+extern crate holochain_json_derive;
+
+use hdk::holochain_persistence_api::cas::content::Address;
+
+use hdk::prelude::*;
+use hdk::AGENT_ADDRESS;
+use hdk_proc_macros::zome;
+
+pub mod transaction;
+
+#[zome]
+mod my_zome {
+
+    #[init]
+    fn init() {
+        Ok(())
+    }
+
+    #[validate_agent]
+    pub fn validate_agent(validation_data: EntryValidationData<AgentId>) {
+        Ok(())
+    }
+
+    #[entry_def]
+    fn transaction_entry_definition() -> ValidatingEntryType {
+        transaction::entry_def()
+    }
+
+    #[zome_fn("hc_public")]
+    fn transfer(receiver_address: Address, amount: u64, reference: String) -> ZomeApiResult<Address> {
+        transaction::transfer(receiver_address, amount, reference)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_balance(agent_address: Address) -> ZomeApiResult<i64> {
+        transaction::get_balance(agent_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_my_balance() -> ZomeApiResult<i64> {
+        transaction::get_balance(AGENT_ADDRESS.to_string().into())
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_transactions(agent_address: Address) -> ZomeApiResult<Vec<Address>> {
+        transaction::get_transactions(agent_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_credit_limit() -> ZomeApiResult<u64> {
+        transaction::get_credit_limit()
+    }
+}
@@ -0,0 +1,195 @@
+/************************ Import Required Libraries */
+use hdk::prelude::*;
+
+use hdk::holochain_core_types::validation::ValidationPackage;
+use hdk::AGENT_ADDRESS;
+use std::convert::TryFrom;
+/******************************************* */
+
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct Transaction {
+    pub spender_address: Address,
+    pub receiver_address: Address,
+    pub amount: u64,
+    pub reference: String,
+    pub sequence: u32, // Number of earlier transactions of the spender, keeps repeated payments distinct
+}
+
+impl Transaction {
+    pub fn new(spender_address: Address, receiver_address: Address, amount: u64, reference: String, sequence: u32) -> Self {
+        Transaction {
+            spender_address,
+            receiver_address,
+            amount,
+            reference,
+            sequence,
+        }
+    }
+
+    pub fn entry(&self) -> Entry {
+        Entry::App("transaction".into(), self.into())
+    }
+}
+
+////////////////////Transaction Entry Definition
+pub fn entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "transaction",
+        description: "a mutual credit payment signed by its spender",
+        sharing: Sharing::Public,
+        validation_package: || {
+            // the spender's earlier payments are read from their chain, not from links they could leave out
+            hdk::ValidationPackageDefinition::ChainEntries
+        },
+        validation: | validation_data: hdk::EntryValidationData<Transaction> | {
+            match validation_data {
+                EntryValidationData::Create {entry, validation_data} => {
+                    if !validation_data.sources().contains(&entry.spender_address) {
+                        return Err(String::from("Only the spender can sign a transaction"));
+                    }
+                    if entry.spender_address == entry.receiver_address {
+                        return Err(String::from("Cannot pay yourself"));
+                    }
+                    if entry.amount == 0 {
+                        return Err(String::from("Transaction amount must be positive"));
+                    }
+                    if entry.amount > i64::MAX as u64 {
+                        return Err(String::from("Transaction amount is too large"));
+                    }
+                    let spent = get_earlier_spending(&validation_data.package, &entry);
+                    if spent.len() != entry.sequence as usize {
+                        return Err(String::from("Transaction sequence does not match the spender's chain"));
+                    }
+                    validate_credit_limit(&entry, &spent)?;
+                    Ok(())
+                },
+                _ => Err(String::from("Transactions cannot be modified or deleted"))
+            }
+        },
+        links: [
+            from!( // transactions an agent spent or received
+                "%agent_id",
+                link_type: "agent->transactions",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, .. } => {
+                            let transaction: Transaction = hdk::utils::get_as_type(link.link.target().clone())?;
+                            let base = link.link.base();
+                            if base != &transaction.spender_address && base != &transaction.receiver_address {
+                                return Err(String::from("Transactions can only be linked from their spender or receiver"));
+                            }
+                            Ok(())
+                        },
+                        hdk::LinkValidationData::LinkRemove { .. } => {
+                            Err(String::from("Transactions cannot be unlinked"))
+                        }
+                    }
+                }
+            )
+        ]
+    )
+}
+
+/*********************** Transaction Validations */
+
+/// The transactions the spender signed before `transaction`, as found on
+/// their source chain.
+fn get_earlier_spending(package: &ValidationPackage, transaction: &Transaction) -> Vec<Transaction> {
+    package
+        .source_chain_entries
+        .clone()
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|entry| match entry {
+            Entry::App(entry_type, value) if entry_type.to_string() == "transaction" => Transaction::try_from(value).ok(),
+            _ => None,
+        })
+        .filter(|spent| spent.spender_address == transaction.spender_address && spent.sequence < transaction.sequence)
+        .collect()
+}
+
+/// Payments received are read from links, which only the other spenders
+/// create, so leaving one out can only lower the balance. Sums are taken in
+/// i128 so that no number of payments can wrap them.
+fn validate_credit_limit(transaction: &Transaction, spent: &Vec<Transaction>) -> ZomeApiResult<()> {
+    let credit_limit = get_credit_limit()? as i128;
+    let received: i128 = load_transactions(&transaction.spender_address)?
+        .iter()
+        .filter(|received| received.receiver_address == transaction.spender_address)
+        .map(|received| received.amount as i128)
+        .sum();
+    let balance = received - spent.iter().map(|spent| spent.amount as i128).sum::<i128>();
+    if balance - (transaction.amount as i128) < -credit_limit {
+        return Err(ZomeApiError::from(String::from(
+            "Transaction exceeds the spender's credit limit",
+        )));
+    }
+    Ok(())
+}
+
+/// Helper Functions
+pub fn get_credit_limit() -> ZomeApiResult<u64> {
+    let credit_limit_json = hdk::property("credit_limit")?;
+    let credit_limit: Result<u64, _> = serde_json::from_str(&credit_limit_json.to_string());
+
+    match credit_limit {
+        Ok(credit_limit) => Ok(credit_limit),
+        Err(_) => Err(ZomeApiError::from(String::from(
+            "Could not get the credit limit for this app"
+        ))),
+    }
+}
+
+fn load_transactions(agent_address: &Address) -> ZomeApiResult<Vec<Transaction>> {
+    hdk::utils::get_links_and_load_type(
+        agent_address,
+        LinkMatch::Exactly("agent->transactions"),
+        LinkMatch::Any,
+    )
+}
+
+pub fn get_transactions(agent_address: Address) -> ZomeApiResult<Vec<Address>> {
+    let links = hdk::get_links(
+        &agent_address,
+        LinkMatch::Exactly("agent->transactions"),
+        LinkMatch::Any,
+    )?;
+    Ok(links.addresses())
+}
+
+pub fn get_balance(agent_address: Address) -> ZomeApiResult<i64> {
+    let balance = load_transactions(&agent_address)?
+        .iter()
+        .fold(0i128, |balance, transaction| {
+            if transaction.receiver_address == agent_address {
+                balance + transaction.amount as i128
+            } else if transaction.spender_address == agent_address {
+                balance - transaction.amount as i128
+            } else {
+                balance
+            }
+        });
+    i64::try_from(balance).map_err(|_| ZomeApiError::from(String::from("Balance is out of range")))
+}
+
+pub fn transfer(receiver_address: Address, amount: u64, reference: String) -> ZomeApiResult<Address> {
+    // every transaction on the agent's own chain is one they spent
+    let sequence = hdk::query("transaction".into(), 0, 0)?.len() as u32;
+
+    let transaction = Transaction::new(
+        AGENT_ADDRESS.to_string().into(),
+        receiver_address.clone(),
+        amount,
+        reference,
+        sequence,
+    );
+    let transaction_address = hdk::commit_entry(&transaction.entry())?;
+
+    hdk::link_entries(&AGENT_ADDRESS, &transaction_address, "agent->transactions", "")?;
+    hdk::link_entries(&receiver_address, &transaction_address, "agent->transactions", "")?;
+
+    Ok(transaction_address)
+}
@@ -0,0 +1,91 @@
+/************************ Import Required Libraries */
+use hdk::prelude::*;
+
+use crate::members;
+use crate::profile;
+use crate::roles;
+use crate::roles::Role;
+use std::convert::TryFrom;
+/******************************************* */
+
+const MAX_PAGE_SIZE: u32 = 50;
+
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct MemberSummary {
+    pub agent_address: Address,
+    pub display_name: Option<String>, // None until the member sets a profile
+    pub roles: Vec<Role>,
+}
+
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct MemberPage {
+    pub members: Vec<MemberSummary>,
+    pub total: u32, // Matches across all pages
+}
+
+/// Helper Functions
+
+/// A name matches when the query is the start of one of its words, or of
+/// several consecutive words.
+fn matches(display_name: &str, query: &str) -> bool {
+    let name = profile::normalise(display_name);
+    name.starts_with(query) || name.contains(&format!(" {}", query))
+}
+
+/// Finds members whose display name matches `query`, optionally keeping only
+/// those holding `role_filter`. An empty query lists every member.
+/// Results are sorted by name, with members without a profile last.
+pub fn search_members(query: String, role_filter: Option<Role>, offset: u32, limit: u32) -> ZomeApiResult<MemberPage> {
+    let query = profile::normalise(&query);
+
+    let mut candidates: Vec<(Address, Option<String>)> = Vec::new();
+    if query.is_empty() {
+        let agent_addresses = match &role_filter {
+            Some(role) => roles::get_role_members(role)?,
+            None => members::get_valid_members()?,
+        };
+        for agent_address in agent_addresses {
+            let display_name = profile::get_profile(&agent_address)?.map(|profile| profile.display_name);
+            candidates.push((agent_address, display_name));
+        }
+    } else {
+        for found in profile::get_profiles_by_prefix(&query)? {
+            let seen = candidates.iter().any(|(agent_address, _)| agent_address == &found.agent_address);
+            if !seen && matches(&found.display_name, &query) && members::is_member_valid(&found.agent_address)? {
+                candidates.push((found.agent_address, Some(found.display_name)));
+            }
+        }
+    }
+
+    let mut results = Vec::new();
+    for (agent_address, display_name) in candidates {
+        let member_roles = roles::get_roles(&agent_address)?;
+        if let Some(role) = &role_filter {
+            if !member_roles.contains(role) {
+                continue;
+            }
+        }
+        results.push(MemberSummary {
+            agent_address,
+            display_name,
+            roles: member_roles,
+        });
+    }
+    results.sort_by_key(|member| {
+        (
+            member.display_name.is_none(),
+            member.display_name.as_ref().map(|name| profile::normalise(name)),
+            member.agent_address.to_string(),
+        )
+    });
+
+    let limit = if limit == 0 { MAX_PAGE_SIZE } else { limit.min(MAX_PAGE_SIZE) };
+    Ok(MemberPage {
+        total: results.len() as u32,
+        members: results
+            .into_iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .collect(),
+    })
+}
@@ -0,0 +1,139 @@
+/************************ Import Required Libraries */
+use hdk::prelude::*;
+
+use crate::members;
+use hdk::AGENT_ADDRESS;
+use std::convert::TryFrom;
+/******************************************* */
+
+/// Lets `invitee_address` join the network. Signed by the inviting member.
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct Invitation {
+    pub invitee_address: Address,
+    pub inviter_address: Address,
+}
+
+impl Invitation {
+    pub fn new(invitee_address: Address, inviter_address: Address) -> Self {
+        Invitation {
+            invitee_address,
+            inviter_address,
+        }
+    }
+
+    pub fn entry(&self) -> Entry {
+        Entry::App("invitation".into(), self.into())
+    }
+}
+
+////////////////////Invitation Entry Definition
+pub fn entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "invitation",
+        description: "an invitation from a member that lets a new agent join",
+        sharing: Sharing::Public,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: | validation_data: hdk::EntryValidationData<Invitation> | {
+            match validation_data {
+                EntryValidationData::Create {entry, validation_data} => {
+                    if !validation_data.sources().contains(&entry.inviter_address) {
+                        return Err(String::from("An invitation must be signed by its inviter"));
+                    }
+                    if entry.invitee_address == entry.inviter_address {
+                        return Err(String::from("Cannot invite yourself"));
+                    }
+                    if !members::is_member_valid(&entry.inviter_address)? {
+                        return Err(String::from("Only members can invite new agents"));
+                    }
+                    Ok(())
+                },
+                _ => Err(String::from("Invitations cannot be modified or deleted"))
+            }
+        }
+    )
+}
+
+//// Anchor Definition : This Anchor is used to find the invitations of an agent
+pub fn anchor_entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "members_anchor",
+        description: "Anchor to all invitations",
+        sharing: Sharing::Public,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: |_validation_data: hdk::EntryValidationData<String>| {
+            Ok(())
+        },
+        links: [
+            to!( // tagged with the invitee's address
+                "invitation",
+                link_type: "invitations",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: |validation_data: hdk::LinkValidationData| {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, .. } => {
+                            let invitation: Invitation = hdk::utils::get_as_type(link.link.target().clone())?;
+                            if link.link.tag() != &invitation.invitee_address.to_string() {
+                                return Err(String::from("An invitation must be tagged with its invitee"));
+                            }
+                            Ok(())
+                        },
+                        hdk::LinkValidationData::LinkRemove { .. } => {
+                            Err(String::from("Invitations cannot be unlinked"))
+                        }
+                    }
+                }
+            )
+        ]
+    )
+}
+
+fn anchor_entry() -> Entry {
+    Entry::App("members_anchor".into(), "invitations".into())
+}
+
+fn anchor_address() -> ZomeApiResult<Address> {
+    hdk::entry_address(&anchor_entry())
+}
+
+/// Helper Functions
+
+/// Invitations were checked against their inviter's membership when they were
+/// committed, so finding one for the agent is enough.
+pub fn is_invited(agent_address: &Address) -> ZomeApiResult<bool> {
+    let agent_tag = agent_address.to_string();
+    let links = hdk::get_links(
+        &anchor_address()?,
+        LinkMatch::Exactly("invitations"),
+        LinkMatch::Exactly(agent_tag.as_str()),
+    )?;
+    Ok(!links.addresses().is_empty())
+}
+
+pub fn get_invited_members() -> ZomeApiResult<Vec<Address>> {
+    let invitations: Vec<Invitation> = hdk::utils::get_links_and_load_type(
+        &anchor_address()?,
+        LinkMatch::Exactly("invitations"),
+        LinkMatch::Any,
+    )?;
+    let mut invited: Vec<Address> = Vec::new();
+    for invitation in invitations {
+        if !invited.contains(&invitation.invitee_address) {
+            invited.push(invitation.invitee_address);
+        }
+    }
+    Ok(invited)
+}
+
+pub fn invite_member(invitee_address: Address) -> ZomeApiResult<Address> {
+    let anchor_address = hdk::commit_entry(&anchor_entry())?;
+    let invitation = Invitation::new(invitee_address.clone(), AGENT_ADDRESS.to_string().into());
+    let invitation_address = hdk::commit_entry(&invitation.entry())?;
+    hdk::link_entries(&anchor_address, &invitation_address, "invitations", invitee_address.to_string().as_str())?;
+    Ok(invitation_address)
+}
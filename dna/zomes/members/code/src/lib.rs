@@ -1,9 +1,13 @@
 #![feature(proc_macro_hygiene)]
+#[macro_use]
 extern crate hdk;
 extern crate hdk_proc_macros;
 extern crate serde;
+#[macro_use]
 extern crate serde_derive;
+#[macro_use]
 extern crate serde_json;
+#[macro_use]
 extern crate holochain_json_derive;
 
 use hdk::holochain_persistence_api::cas::content::Address;
@@ -11,7 +15,12 @@ use hdk::holochain_persistence_api::cas::content::Address;
 use hdk::prelude::*;
 use hdk_proc_macros::zome;
 
+pub mod directory;
+pub mod invitation;
 pub mod members;
+pub mod profile;
+pub mod revocation;
+pub mod roles;
 
 #[zome]
 mod my_zome {
@@ -25,6 +34,9 @@ mod my_zome {
     pub fn validate_agent(validation_data: EntryValidationData<AgentId>) {
         match validation_data {
             EntryValidationData::Create { validation_data, ..  } => {
+                // hdk 0.0.42 hands this callback only the AgentId and its header,
+                // with no membrane proof, so the invitation is found on the DHT
+                // by the joining agent's address instead of being read from a proof
                 let agent_address = validation_data.package.chain_header.entry_address();
                 match members::is_member_valid(&agent_address)? {
                     true => Ok(()),
@@ -35,6 +47,86 @@ mod my_zome {
         }
     }
 
+    #[entry_def]
+    fn members_anchor_entry_definition() -> ValidatingEntryType {
+        invitation::anchor_entry_def()
+    }
+
+    #[entry_def]
+    fn invitation_entry_definition() -> ValidatingEntryType {
+        invitation::entry_def()
+    }
+
+    #[zome_fn("hc_public")]
+    fn invite_member(invitee_address: Address) -> ZomeApiResult<Address> {
+        invitation::invite_member(invitee_address)
+    }
+
+    #[entry_def]
+    fn role_assignment_entry_definition() -> ValidatingEntryType {
+        roles::entry_def()
+    }
+
+    #[zome_fn("hc_public")]
+    fn grant_role(agent_address: Address, role: roles::Role) -> ZomeApiResult<Address> {
+        roles::grant_role(agent_address, role)
+    }
+
+    #[zome_fn("hc_public")]
+    fn revoke_role(agent_address: Address, role: roles::Role) -> ZomeApiResult<Vec<Address>> {
+        roles::revoke_role(agent_address, role)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_roles(agent_address: Address) -> ZomeApiResult<Vec<roles::Role>> {
+        roles::get_roles(&agent_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn has_role(agent_address: Address, role: roles::Role) -> ZomeApiResult<bool> {
+        roles::has_role(&agent_address, &role)
+    }
+
+    #[entry_def]
+    fn revocation_entry_definition() -> ValidatingEntryType {
+        revocation::entry_def()
+    }
+
+    #[zome_fn("hc_public")]
+    fn revoke_membership(agent_address: Address, reason: String) -> ZomeApiResult<Address> {
+        revocation::revoke_membership(agent_address, reason)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_revocation(agent_address: Address) -> ZomeApiResult<Option<revocation::Revocation>> {
+        revocation::get_revocation(&agent_address)
+    }
+
+    #[entry_def]
+    fn profile_entry_definition() -> ValidatingEntryType {
+        profile::entry_def()
+    }
+
+    #[zome_fn("hc_public")]
+    fn set_profile(display_name: String, bio: String, avatar_url: Option<String>, links: Vec<String>) -> ZomeApiResult<Address> {
+        profile::set_profile(display_name, bio, avatar_url, links)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_profile(agent_address: Address) -> ZomeApiResult<Option<profile::Profile>> {
+        profile::get_profile(&agent_address)
+    }
+
+    #[zome_fn("hc_public")]
+    fn get_profiles(agent_addresses: Vec<Address>) -> ZomeApiResult<Vec<profile::Profile>> {
+        profile::get_profiles(agent_addresses)
+    }
+
+    #[zome_fn("hc_public")]
+    fn search_members(query: String, role_filter: Option<roles::Role>, offset: u32, limit: u32) -> ZomeApiResult<directory::MemberPage> {
+        directory::search_members(query, role_filter, offset, limit)
+    }
+
     #[zome_fn("hc_public")]
     fn get_valid_members() -> ZomeApiResult<Vec<Address>> {
         members::get_valid_members()
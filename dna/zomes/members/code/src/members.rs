@@ -1,7 +1,10 @@
 use hdk::prelude::*;
 
+use crate::invitation;
+use crate::revocation;
 
-pub fn get_valid_members() -> ZomeApiResult<Vec<Address>> {
+/// The members listed in the DNA properties when the network was created.
+pub fn get_seed_members() -> ZomeApiResult<Vec<Address>> {
     let valid_members_json = hdk::property("valid_members")?;
     let valid_members: Result<Vec<Address>, _> = serde_json::from_str(&valid_members_json.to_string());
 
@@ -13,7 +16,22 @@ pub fn get_valid_members() -> ZomeApiResult<Vec<Address>> {
     }
 }
 
+pub fn get_valid_members() -> ZomeApiResult<Vec<Address>> {
+    let mut valid_members = get_seed_members()?;
+    for invited in invitation::get_invited_members()? {
+        if !valid_members.contains(&invited) {
+            valid_members.push(invited);
+        }
+    }
+    let revoked = revocation::get_revoked_members()?;
+    valid_members.retain(|member| !revoked.contains(member));
+    Ok(valid_members)
+}
+
 pub fn is_member_valid(agent_address: &Address) -> ZomeApiResult<bool> {
-    let valid_members = get_valid_members()?;
-    Ok(valid_members.contains(agent_address))
-}
\ No newline at end of file
+    if revocation::is_revoked(agent_address)? {
+        return Ok(false);
+    }
+    let valid_members = get_seed_members()?;
+    Ok(valid_members.contains(agent_address) || invitation::is_invited(agent_address)?)
+}
@@ -0,0 +1,266 @@
+/************************ Import Required Libraries */
+use hdk::prelude::*;
+
+use hdk::AGENT_ADDRESS;
+use std::convert::TryFrom;
+/******************************************* */
+
+const MAX_DISPLAY_NAME_LENGTH: usize = 50;
+const MAX_BIO_LENGTH: usize = 1000;
+const MAX_LINKS: usize = 10;
+const NAME_PREFIX_LENGTH: usize = 3;
+
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct Profile {
+    pub agent_address: Address,
+    pub display_name: String,
+    pub bio: String,
+    pub avatar_url: Option<String>,
+    pub links: Vec<String>, // Personal website, social accounts...
+}
+
+impl Profile {
+    pub fn new(agent_address: Address, display_name: String, bio: String, avatar_url: Option<String>, links: Vec<String>) -> Self {
+        Profile {
+            agent_address,
+            display_name,
+            bio,
+            avatar_url,
+            links,
+        }
+    }
+
+    pub fn entry(&self) -> Entry {
+        Entry::App("profile".into(), self.into())
+    }
+}
+
+////////////////////Profile Entry Definition
+pub fn entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "profile",
+        description: "how a member presents themselves to others",
+        sharing: Sharing::Public,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: | validation_data: hdk::EntryValidationData<Profile> | {
+            match validation_data {
+                EntryValidationData::Create {entry, validation_data} => {
+                    if !validation_data.sources().contains(&entry.agent_address) {
+                        return Err(String::from("Only the owner can create their profile"));
+                    }
+                    validate_profile(&entry)
+                },
+                EntryValidationData::Modify {new_entry, old_entry, validation_data, ..} => {
+                    if new_entry.agent_address != old_entry.agent_address {
+                        return Err(String::from("Cannot change the owner of a profile"));
+                    }
+                    if !validation_data.sources().contains(&old_entry.agent_address) {
+                        return Err(String::from("Only the owner can edit their profile"));
+                    }
+                    validate_profile(&new_entry)
+                },
+                EntryValidationData::Delete {old_entry, validation_data, ..} => {
+                    if !validation_data.sources().contains(&old_entry.agent_address) {
+                        return Err(String::from("Only the owner can delete their profile"));
+                    }
+                    Ok(())
+                }
+            }
+        },
+        links: [
+            from!( // one live profile per agent
+                "%agent_id",
+                link_type: "agent->profile",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, validation_data } => {
+                            let base = link.link.base();
+                            if !validation_data.sources().contains(base) {
+                                return Err(String::from("Only the owner can link their profile"));
+                            }
+                            let profile: Profile = hdk::utils::get_as_type(link.link.target().clone())?;
+                            if &profile.agent_address != base {
+                                return Err(String::from("A profile can only be linked from its owner"));
+                            }
+                            let existing = get_profile_address(base)?;
+                            if existing.is_some() && existing.as_ref() != Some(link.link.target()) {
+                                return Err(String::from("This agent already has a profile"));
+                            }
+                            Ok(())
+                        },
+                        hdk::LinkValidationData::LinkRemove { link, validation_data } => {
+                            if !validation_data.sources().contains(link.link.base()) {
+                                return Err(String::from("Only the owner can unlink their profile"));
+                            }
+                            Ok(())
+                        }
+                    }
+                }
+            ),
+            from!( // tagged with the profile's owner
+                "members_anchor",
+                link_type: "profiles_by_name",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, validation_data } => {
+                            let profile: Profile = hdk::utils::get_as_type(link.link.target().clone())?;
+                            if link.link.tag() != &profile.agent_address.to_string() {
+                                return Err(String::from("A name index must be tagged with the profile's owner"));
+                            }
+                            if !validation_data.sources().contains(&profile.agent_address) {
+                                return Err(String::from("Only the owner can index their profile"));
+                            }
+                            Ok(())
+                        },
+                        hdk::LinkValidationData::LinkRemove { link, validation_data } => {
+                            if !validation_data.sources().contains(&Address::from(link.link.tag().clone())) {
+                                return Err(String::from("Only the owner can unindex their profile"));
+                            }
+                            Ok(())
+                        }
+                    }
+                }
+            )
+        ]
+    )
+}
+
+/*********************** Profile Validations */
+fn validate_profile(profile: &Profile) -> Result<(), String> {
+    if profile.display_name.trim().is_empty() {
+        return Err(String::from("A profile needs a display name"));
+    }
+    if profile.display_name.chars().count() > MAX_DISPLAY_NAME_LENGTH {
+        return Err(String::from("Display name is too long"));
+    }
+    if profile.bio.chars().count() > MAX_BIO_LENGTH {
+        return Err(String::from("Bio is too long"));
+    }
+    if profile.links.len() > MAX_LINKS {
+        return Err(String::from("Too many profile links"));
+    }
+    let urls = profile.avatar_url.iter().chain(profile.links.iter());
+    for url in urls {
+        if !url.starts_with("https://") && !url.starts_with("http://") {
+            return Err(format!("Invalid url {}", url));
+        }
+    }
+    Ok(())
+}
+
+/// Helper Functions
+
+/// Lowercases the name and collapses its whitespace, so searches ignore case
+/// and spacing.
+pub fn normalise(name: &str) -> String {
+    name.split_whitespace()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// The prefixes, up to `NAME_PREFIX_LENGTH` characters, of every word in the name.
+fn name_prefixes(display_name: &str) -> Vec<String> {
+    let mut prefixes = Vec::new();
+    for word in normalise(display_name).split(' ') {
+        for length in 1..=NAME_PREFIX_LENGTH.min(word.chars().count()) {
+            let prefix: String = word.chars().take(length).collect();
+            if !prefixes.contains(&prefix) {
+                prefixes.push(prefix);
+            }
+        }
+    }
+    prefixes
+}
+
+/// The anchor under which profiles are found by a name prefix.
+pub fn name_anchor_entry(prefix: &str) -> Entry {
+    Entry::App("members_anchor".into(), format!("name:{}", prefix).into())
+}
+
+/// Profiles whose name has a word starting with the first letters of `query`.
+/// The caller still has to match the rest of the query.
+pub fn get_profiles_by_prefix(query: &str) -> ZomeApiResult<Vec<Profile>> {
+    let prefix: String = normalise(query).chars().take_while(|c| *c != ' ').take(NAME_PREFIX_LENGTH).collect();
+    if prefix.is_empty() {
+        return Ok(Vec::new());
+    }
+    hdk::utils::get_links_and_load_type(
+        &hdk::entry_address(&name_anchor_entry(&prefix))?,
+        LinkMatch::Exactly("profiles_by_name"),
+        LinkMatch::Any,
+    )
+}
+
+fn index_name(profile_address: &Address, old_name: Option<&str>, new_name: &str) -> ZomeApiResult<()> {
+    let agent_tag = AGENT_ADDRESS.to_string();
+    let old_prefixes = old_name.map(name_prefixes).unwrap_or_default();
+    let new_prefixes = name_prefixes(new_name);
+
+    for prefix in old_prefixes.iter().filter(|prefix| !new_prefixes.contains(prefix)) {
+        let anchor_address = hdk::entry_address(&name_anchor_entry(prefix))?;
+        hdk::remove_link(&anchor_address, profile_address, "profiles_by_name", agent_tag.as_str())?;
+    }
+    for prefix in new_prefixes.iter().filter(|prefix| !old_prefixes.contains(prefix)) {
+        let anchor_address = hdk::commit_entry(&name_anchor_entry(prefix))?;
+        hdk::link_entries(&anchor_address, profile_address, "profiles_by_name", agent_tag.as_str())?;
+    }
+    Ok(())
+}
+
+fn get_profile_address(agent_address: &Address) -> ZomeApiResult<Option<Address>> {
+    let links = hdk::get_links(
+        agent_address,
+        LinkMatch::Exactly("agent->profile"),
+        LinkMatch::Any,
+    )?;
+    Ok(links.addresses().into_iter().next())
+}
+
+/// Creates the caller's profile, or updates it if they already have one.
+pub fn set_profile(display_name: String, bio: String, avatar_url: Option<String>, links: Vec<String>) -> ZomeApiResult<Address> {
+    let agent_address: Address = AGENT_ADDRESS.to_string().into();
+    let profile = Profile::new(agent_address.clone(), display_name, bio, avatar_url, links);
+
+    match get_profile_address(&agent_address)? {
+        Some(profile_address) => {
+            let old_profile: Profile = hdk::utils::get_as_type(profile_address.clone())?;
+            let address = hdk::update_entry(profile.entry(), &profile_address)?;
+            // The index keeps pointing at the first version, which resolves to the latest
+            index_name(&profile_address, Some(&old_profile.display_name), &profile.display_name)?;
+            Ok(address)
+        },
+        None => {
+            let profile_address = hdk::commit_entry(&profile.entry())?;
+            hdk::link_entries(&agent_address, &profile_address, "agent->profile", "")?;
+            index_name(&profile_address, None, &profile.display_name)?;
+            Ok(profile_address)
+        }
+    }
+}
+
+pub fn get_profile(agent_address: &Address) -> ZomeApiResult<Option<Profile>> {
+    match get_profile_address(agent_address)? {
+        Some(profile_address) => Ok(Some(hdk::utils::get_as_type(profile_address)?)),
+        None => Ok(None),
+    }
+}
+
+/// Agents without a profile are left out.
+pub fn get_profiles(agent_addresses: Vec<Address>) -> ZomeApiResult<Vec<Profile>> {
+    let mut profiles = Vec::new();
+    for agent_address in agent_addresses {
+        if let Some(profile) = get_profile(&agent_address)? {
+            profiles.push(profile);
+        }
+    }
+    Ok(profiles)
+}
@@ -0,0 +1,123 @@
+/************************ Import Required Libraries */
+use hdk::prelude::*;
+
+use crate::roles;
+use crate::roles::Role;
+use hdk::AGENT_ADDRESS;
+use std::convert::TryFrom;
+/******************************************* */
+
+/// Ends the membership of `agent_address`. Signed by an admin.
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct Revocation {
+    pub agent_address: Address,
+    pub reason: String,
+    pub revoked_by: Address,
+}
+
+impl Revocation {
+    pub fn new(agent_address: Address, reason: String, revoked_by: Address) -> Self {
+        Revocation {
+            agent_address,
+            reason,
+            revoked_by,
+        }
+    }
+
+    pub fn entry(&self) -> Entry {
+        Entry::App("revocation".into(), self.into())
+    }
+}
+
+////////////////////Revocation Entry Definition
+pub fn entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "revocation",
+        description: "an admin's decision to end an agent's membership",
+        sharing: Sharing::Public,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: | validation_data: hdk::EntryValidationData<Revocation> | {
+            match validation_data {
+                EntryValidationData::Create {entry, validation_data} => {
+                    if !validation_data.sources().contains(&entry.revoked_by) {
+                        return Err(String::from("A revocation must be signed by the admin issuing it"));
+                    }
+                    if !roles::has_role(&entry.revoked_by, &Role::Admin)? {
+                        return Err(String::from("Only admins can revoke memberships"));
+                    }
+                    if entry.reason.trim().is_empty() {
+                        return Err(String::from("A revocation needs a reason"));
+                    }
+                    Ok(())
+                },
+                _ => Err(String::from("Revocations cannot be modified or deleted"))
+            }
+        },
+        links: [
+            from!( // tagged with the revoked agent
+                "members_anchor",
+                link_type: "revocations",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    match validation_data {
+                        hdk::LinkValidationData::LinkAdd { link, validation_data } => {
+                            let revocation: Revocation = hdk::utils::get_as_type(link.link.target().clone())?;
+                            if link.link.tag() != &revocation.agent_address.to_string() {
+                                return Err(String::from("A revocation must be tagged with the revoked agent"));
+                            }
+                            if !validation_data.sources().contains(&revocation.revoked_by) {
+                                return Err(String::from("A revocation must be linked by the admin issuing it"));
+                            }
+                            Ok(())
+                        },
+                        hdk::LinkValidationData::LinkRemove { .. } => {
+                            Err(String::from("Revocations cannot be unlinked"))
+                        }
+                    }
+                }
+            )
+        ]
+    )
+}
+
+/// Helper Functions
+fn anchor_entry() -> Entry {
+    Entry::App("members_anchor".into(), "revocations".into())
+}
+
+pub fn get_revocation(agent_address: &Address) -> ZomeApiResult<Option<Revocation>> {
+    let agent_tag = agent_address.to_string();
+    let revocations: Vec<Revocation> = hdk::utils::get_links_and_load_type(
+        &hdk::entry_address(&anchor_entry())?,
+        LinkMatch::Exactly("revocations"),
+        LinkMatch::Exactly(agent_tag.as_str()),
+    )?;
+    Ok(revocations
+        .into_iter()
+        .find(|revocation| revocation.agent_address == *agent_address))
+}
+
+pub fn is_revoked(agent_address: &Address) -> ZomeApiResult<bool> {
+    Ok(get_revocation(agent_address)?.is_some())
+}
+
+pub fn revoke_membership(agent_address: Address, reason: String) -> ZomeApiResult<Address> {
+    let anchor_address = hdk::commit_entry(&anchor_entry())?;
+    let revocation = Revocation::new(agent_address.clone(), reason, AGENT_ADDRESS.to_string().into());
+    let revocation_address = hdk::commit_entry(&revocation.entry())?;
+    hdk::link_entries(&anchor_address, &revocation_address, "revocations", agent_address.to_string().as_str())?;
+    Ok(revocation_address)
+}
+
+pub fn get_revoked_members() -> ZomeApiResult<Vec<Address>> {
+    let revocations: Vec<Revocation> = hdk::utils::get_links_and_load_type(
+        &hdk::entry_address(&anchor_entry())?,
+        LinkMatch::Exactly("revocations"),
+        LinkMatch::Any,
+    )?;
+    Ok(revocations.into_iter().map(|revocation| revocation.agent_address).collect())
+}
@@ -0,0 +1,234 @@
+/************************ Import Required Libraries */
+use hdk::prelude::*;
+
+use crate::members;
+use hdk::AGENT_ADDRESS;
+use std::convert::TryFrom;
+/******************************************* */
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Role {
+    Admin,
+    Teacher,
+    Student,
+    Moderator,
+}
+
+/// A role an admin granted to a member.
+#[derive(Serialize, Deserialize, Debug, DefaultJson, Clone)]
+pub struct RoleAssignment {
+    pub agent_address: Address,
+    pub role: Role,
+    pub granted_by: Address,
+}
+
+impl RoleAssignment {
+    pub fn new(agent_address: Address, role: Role, granted_by: Address) -> Self {
+        RoleAssignment {
+            agent_address,
+            role,
+            granted_by,
+        }
+    }
+
+    pub fn entry(&self) -> Entry {
+        Entry::App("role_assignment".into(), self.into())
+    }
+}
+
+////////////////////Role Assignment Entry Definition
+pub fn entry_def() -> ValidatingEntryType {
+    entry!(
+        name: "role_assignment",
+        description: "a role granted to a member by an admin",
+        sharing: Sharing::Public,
+        validation_package: || {
+            hdk::ValidationPackageDefinition::Entry
+        },
+        validation: | validation_data: hdk::EntryValidationData<RoleAssignment> | {
+            match validation_data {
+                EntryValidationData::Create {entry, validation_data} => {
+                    if !validation_data.sources().contains(&entry.granted_by) {
+                        return Err(String::from("A role must be signed by the admin granting it"));
+                    }
+                    validate_admin(&entry.granted_by)?;
+                    if !members::is_member_valid(&entry.agent_address)? {
+                        return Err(String::from("Roles can only be granted to members"));
+                    }
+                    Ok(())
+                },
+                EntryValidationData::Modify {..} => {
+                    Err(String::from("Cannot modify a role, revoke it and grant another"))
+                },
+                EntryValidationData::Delete {validation_data, ..} => {
+                    for source in validation_data.sources() {
+                        if validate_admin(&source).is_ok() {
+                            return Ok(());
+                        }
+                    }
+                    Err(String::from("Only admins can revoke roles"))
+                }
+            }
+        },
+        links: [
+            from!( // tagged with the agent holding the role
+                "members_anchor",
+                link_type: "role_assignments",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    validate_assignment_link(validation_data)
+                }
+            ),
+            from!( // indexes the assignment under its role, tagged with the agent
+                "members_anchor",
+                link_type: "role_members",
+                validation_package: || {
+                    hdk::ValidationPackageDefinition::Entry
+                },
+                validation: | validation_data: hdk::LinkValidationData | {
+                    validate_assignment_link(validation_data)
+                }
+            )
+        ]
+    )
+}
+
+/*********************** Role Validations */
+fn validate_admin(agent_address: &Address) -> ZomeApiResult<()> {
+    if !has_role(agent_address, &Role::Admin)? {
+        return Err(ZomeApiError::from(String::from("Only admins can grant roles")));
+    }
+    Ok(())
+}
+
+/// Assignments are found by their tag, so the tag must name the agent the
+/// assignment was granted to and only the granting admin can add it.
+fn validate_assignment_link(validation_data: hdk::LinkValidationData) -> Result<(), String> {
+    match validation_data {
+        hdk::LinkValidationData::LinkAdd { link, validation_data } => {
+            let assignment: RoleAssignment = hdk::utils::get_as_type(link.link.target().clone())?;
+            if link.link.tag() != &assignment.agent_address.to_string() {
+                return Err(String::from("A role must be tagged with the agent holding it"));
+            }
+            if !validation_data.sources().contains(&assignment.granted_by) {
+                return Err(String::from("A role must be linked by the admin granting it"));
+            }
+            Ok(())
+        },
+        hdk::LinkValidationData::LinkRemove { validation_data, .. } => {
+            for source in validation_data.sources() {
+                if validate_admin(&source).is_ok() {
+                    return Ok(());
+                }
+            }
+            Err(String::from("Only admins can revoke roles"))
+        }
+    }
+}
+
+/// Helper Functions
+fn anchor_entry() -> Entry {
+    Entry::App("members_anchor".into(), "roles".into())
+}
+
+fn role_anchor_entry(role: &Role) -> Entry {
+    Entry::App("members_anchor".into(), format!("role:{:?}", role).into())
+}
+
+fn get_assignments(agent_address: &Address) -> ZomeApiResult<Vec<(Address, RoleAssignment)>> {
+    let agent_tag = agent_address.to_string();
+    let links = hdk::get_links(
+        &hdk::entry_address(&anchor_entry())?,
+        LinkMatch::Exactly("role_assignments"),
+        LinkMatch::Exactly(agent_tag.as_str()),
+    )?;
+    let mut assignments = Vec::new();
+    for assignment_address in links.addresses() {
+        // revoked assignments are deleted and no longer load
+        if let Ok(assignment) = hdk::utils::get_as_type::<RoleAssignment>(assignment_address.clone()) {
+            if assignment.agent_address == *agent_address {
+                assignments.push((assignment_address, assignment));
+            }
+        }
+    }
+    Ok(assignments)
+}
+
+/// Seed members from the DNA properties are the first admins and teachers;
+/// every member is a student.
+pub fn get_roles(agent_address: &Address) -> ZomeApiResult<Vec<Role>> {
+    if !members::is_member_valid(agent_address)? {
+        return Ok(Vec::new());
+    }
+
+    let mut roles = vec![Role::Student];
+    if members::get_seed_members()?.contains(agent_address) {
+        roles.push(Role::Admin);
+        roles.push(Role::Teacher);
+    }
+    for (_, assignment) in get_assignments(agent_address)? {
+        if !roles.contains(&assignment.role) {
+            roles.push(assignment.role);
+        }
+    }
+    Ok(roles)
+}
+
+pub fn has_role(agent_address: &Address, role: &Role) -> ZomeApiResult<bool> {
+    Ok(get_roles(agent_address)?.contains(role))
+}
+
+pub fn grant_role(agent_address: Address, role: Role) -> ZomeApiResult<Address> {
+    let anchor_address = hdk::commit_entry(&anchor_entry())?;
+    let assignment = RoleAssignment::new(agent_address.clone(), role, AGENT_ADDRESS.to_string().into());
+    let assignment_address = hdk::commit_entry(&assignment.entry())?;
+    hdk::link_entries(&anchor_address, &assignment_address, "role_assignments", agent_address.to_string().as_str())?;
+    let role_anchor_address = hdk::commit_entry(&role_anchor_entry(&assignment.role))?;
+    hdk::link_entries(&role_anchor_address, &assignment_address, "role_members", agent_address.to_string().as_str())?;
+    Ok(assignment_address)
+}
+
+pub fn revoke_role(agent_address: Address, role: Role) -> ZomeApiResult<Vec<Address>> {
+    let anchor_address = hdk::entry_address(&anchor_entry())?;
+    let mut revoked = Vec::new();
+    for (assignment_address, assignment) in get_assignments(&agent_address)? {
+        if assignment.role == role {
+            hdk::remove_link(&anchor_address, &assignment_address, "role_assignments", agent_address.to_string().as_str())?;
+            let role_anchor_address = hdk::entry_address(&role_anchor_entry(&role))?;
+            hdk::remove_link(&role_anchor_address, &assignment_address, "role_members", agent_address.to_string().as_str())?;
+            revoked.push(hdk::remove_entry(&assignment_address)?);
+        }
+    }
+    Ok(revoked)
+}
+
+/// Every member holding `role`, including the implicit roles from `get_roles`.
+pub fn get_role_members(role: &Role) -> ZomeApiResult<Vec<Address>> {
+    let mut role_members = match role {
+        Role::Student => return members::get_valid_members(),
+        Role::Admin | Role::Teacher => members::get_seed_members()?,
+        Role::Moderator => Vec::new(),
+    };
+    let links = hdk::get_links(
+        &hdk::entry_address(&role_anchor_entry(role))?,
+        LinkMatch::Exactly("role_members"),
+        LinkMatch::Any,
+    )?;
+    for assignment_address in links.addresses() {
+        if let Ok(assignment) = hdk::utils::get_as_type::<RoleAssignment>(assignment_address) {
+            if assignment.role == *role && !role_members.contains(&assignment.agent_address) {
+                role_members.push(assignment.agent_address);
+            }
+        }
+    }
+
+    let mut valid_members = Vec::new();
+    for agent_address in role_members {
+        if members::is_member_valid(&agent_address)? {
+            valid_members.push(agent_address);
+        }
+    }
+    Ok(valid_members)
+}
@@ -58,7 +58,6 @@ export const resolvers = {
         ZOME_NAME,
         'create_course'
       )({
-        timestamp: getTimestamp(),
         title
       });
 
@@ -94,7 +93,6 @@ export const resolvers = {
         ZOME_NAME,
         'create_module'
       )({
-        timestamp: getTimestamp(),
         course_address: courseId,
         title
       });
@@ -136,7 +134,6 @@ export const resolvers = {
         ZOME_NAME,
         'create_content'
       )({
-        timestamp: getTimestamp(),
         name: content.name,
         module_address: moduleId,
         url: content.url,
@@ -190,7 +187,3 @@ export const resolvers = {
     }
   }
 };
-
-function getTimestamp() {
-  return Math.floor(Date.now() / 1000);
-}
\ No newline at end of file